@@ -0,0 +1,75 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::Card;
+
+/// Gives every card a `CardId` that, unlike its `Entity`, can be written to
+/// disk and mean the same card when read back.
+pub struct CardIdPlugin;
+
+impl Plugin for CardIdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NextCardId>()
+            .init_resource::<CardIds>()
+            // Cards spawned during Update exist by now.
+            .add_systems(PostUpdate, (assign_card_ids, index_card_ids).chain());
+    }
+}
+
+/// A card's stable identity. Never reused within a session.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CardId(pub u64);
+
+/// The id the next card will get. Restoring a save should move this past
+/// every id it contains.
+#[derive(Resource, Default)]
+pub struct NextCardId(pub u64);
+
+impl NextCardId {
+    fn take(&mut self) -> CardId {
+        let id = CardId(self.0);
+        self.0 += 1;
+        id
+    }
+}
+
+/// Which entity currently holds each `CardId`.
+#[derive(Resource, Default)]
+pub struct CardIds(HashMap<CardId, Entity>);
+
+impl CardIds {
+    pub fn get(&self, id: CardId) -> Option<Entity> {
+        self.0.get(&id).copied()
+    }
+
+    /// Drops whatever id `entity` held.
+    pub fn forget(&mut self, entity: Entity) {
+        self.0.retain(|_, held_by| *held_by != entity);
+    }
+}
+
+/// Cards spawned with an id of their own, say from a save, keep it.
+fn assign_card_ids(
+    cards: Query<Entity, (Added<Card>, Without<CardId>)>,
+    mut next: ResMut<NextCardId>,
+    mut commands: Commands,
+) {
+    for entity in &cards {
+        commands.entity(entity).insert(next.take());
+    }
+}
+
+fn index_card_ids(
+    added: Query<(Entity, &CardId), Added<CardId>>,
+    mut removed: RemovedComponents<CardId>,
+    mut ids: ResMut<CardIds>,
+    mut next: ResMut<NextCardId>,
+) {
+    for entity in removed.read() {
+        ids.forget(entity);
+    }
+
+    for (entity, id) in &added {
+        ids.0.insert(*id, entity);
+        next.0 = next.0.max(id.0 + 1);
+    }
+}
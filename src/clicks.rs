@@ -0,0 +1,62 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+
+use crate::WordCursor;
+
+/// How close in time and space two clicks must be to count as a double-click.
+#[derive(Resource)]
+pub struct DoubleClickConfig {
+    pub interval: f32,
+    pub max_distance: f32,
+}
+
+impl Default for DoubleClickConfig {
+    fn default() -> Self {
+        Self {
+            interval: 0.3,
+            max_distance: 8.0,
+        }
+    }
+}
+
+/// Time and world position of the last click on each entity.
+#[derive(Resource, Default)]
+pub struct ClickTracker(pub HashMap<Entity, (f32, Vec2)>);
+
+/// Shared double-click detection, so every system agrees on what a double-click is.
+#[derive(SystemParam)]
+pub struct Clicks<'w> {
+    tracker: ResMut<'w, ClickTracker>,
+    config: Res<'w, DoubleClickConfig>,
+    time: Res<'w, Time>,
+    cursor: Res<'w, WordCursor>,
+}
+
+impl Clicks<'_> {
+    /// Records a click on `entity` at the cursor, returning whether it completes
+    /// a double-click. A completed double-click is forgotten, so a triple-click
+    /// doesn't count twice.
+    pub fn register_click(&mut self, entity: Entity) -> bool {
+        let now = self.time.elapsed_seconds();
+        let pos = self.cursor.0;
+        let interval = self.config.interval;
+        let max_distance = self.config.max_distance;
+
+        self.tracker
+            .0
+            .retain(|_, (clicked_at, _)| now - *clicked_at <= interval);
+
+        let double = self
+            .tracker
+            .0
+            .get(&entity)
+            .is_some_and(|(_, clicked_pos)| clicked_pos.distance(pos) <= max_distance);
+
+        if double {
+            self.tracker.0.remove(&entity);
+        } else {
+            self.tracker.0.insert(entity, (now, pos));
+        }
+
+        double
+    }
+}
@@ -0,0 +1,300 @@
+use bevy::{input::InputSystem, prelude::*};
+use std::str::FromStr;
+
+use crate::{
+    card_id::CardId,
+    deal::{DealFacing, DealSource},
+    deck::{CardData, RecallOrder},
+    rules::PlacementRule,
+    CompleteAnimation, SpawnFrom,
+};
+
+/// Log lines kept on screen.
+const CONSOLE_LOG_LINES: usize = 12;
+
+/// A developer command typed into the console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    /// `spawn AS`: a face-up card at the cursor.
+    Spawn(CardData),
+    /// `clear`: removes every card on the board.
+    Clear,
+    /// `shuffle [seed]`: shuffles the deck, reproducibly when seeded.
+    Shuffle(Option<u64>),
+    /// `sort`: puts the deck in new-deck order.
+    Sort,
+    /// `deal 7`: deals that many cards with the current deal pattern.
+    Deal(usize),
+    /// `deal-from bottom`: which end of the deck later deals draw from.
+    DealFrom(DealSource),
+    /// `deal-facing down`: which side up later deals land.
+    DealFacing(DealFacing),
+    /// `recall-order sorted`: where cards turned back into the deck go.
+    RecallOrder(RecallOrder),
+    /// `complete shrink`: how completed sets leave their foundation.
+    CompleteAnimation(CompleteAnimation),
+    /// `spawn-from stock`: where cards spawned at the cursor slide in from.
+    SpawnFrom(SpawnFrom),
+    /// `rule any`: which cards may be placed on which.
+    PlacementRule(PlacementRule),
+    /// `tag 12 wild`: gives the card with that `CardId` a tag.
+    Tag(CardId, String),
+    /// `untag 12 wild`: takes a tag off the card with that `CardId`.
+    Untag(CardId, String),
+    /// `tagged wild`: lists the ids of the cards with a tag.
+    Tagged(String),
+    /// `inspect 12`: shows the card with that `CardId` in the inspector.
+    Inspect(CardId),
+    /// `pile`: lists the card ids in the pile under the cursor.
+    Pile,
+    /// `compact`: tidies every pile back into its exact fan.
+    Compact,
+    /// `save`: writes the board to the save file.
+    Save,
+    /// `load`: replaces the board with the one in the save file.
+    Load,
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("empty command".to_owned());
+        };
+        let args = words.collect::<Vec<_>>();
+
+        match (name, args.as_slice()) {
+            ("spawn", [card]) => card
+                .parse()
+                .map(ConsoleCommand::Spawn)
+                .map_err(|err| err.to_string()),
+            ("clear", []) => Ok(ConsoleCommand::Clear),
+            ("shuffle", []) => Ok(ConsoleCommand::Shuffle(None)),
+            ("shuffle", [seed]) => seed
+                .parse()
+                .map(|seed| ConsoleCommand::Shuffle(Some(seed)))
+                .map_err(|_| format!("invalid seed {seed:?}")),
+            ("sort", []) => Ok(ConsoleCommand::Sort),
+            ("deal", [count]) => count
+                .parse()
+                .map(ConsoleCommand::Deal)
+                .map_err(|_| format!("invalid card count {count:?}")),
+            ("deal-from", ["top"]) => Ok(ConsoleCommand::DealFrom(DealSource::Top)),
+            ("deal-from", ["bottom"]) => Ok(ConsoleCommand::DealFrom(DealSource::Bottom)),
+            ("deal-facing", ["up"]) => Ok(ConsoleCommand::DealFacing(DealFacing::FaceUp)),
+            ("deal-facing", ["down"]) => Ok(ConsoleCommand::DealFacing(DealFacing::FaceDown)),
+            ("recall-order", [order]) => match *order {
+                "append" => Ok(ConsoleCommand::RecallOrder(RecallOrder::Append)),
+                "prepend" => Ok(ConsoleCommand::RecallOrder(RecallOrder::Prepend)),
+                "sorted" => Ok(ConsoleCommand::RecallOrder(RecallOrder::Sorted)),
+                "reverse" => Ok(ConsoleCommand::RecallOrder(RecallOrder::ReverseOfDraw)),
+                _ => Err(format!("unknown recall order {order:?}")),
+            },
+            ("complete", [animation]) => match *animation {
+                "display" => Ok(ConsoleCommand::CompleteAnimation(
+                    CompleteAnimation::Display,
+                )),
+                "fly-off" => Ok(ConsoleCommand::CompleteAnimation(CompleteAnimation::FlyOff)),
+                "fade-out" => Ok(ConsoleCommand::CompleteAnimation(
+                    CompleteAnimation::FadeOut,
+                )),
+                "shrink" => Ok(ConsoleCommand::CompleteAnimation(CompleteAnimation::Shrink)),
+                _ => Err(format!("unknown complete animation {animation:?}")),
+            },
+            ("spawn-from", [from]) => match *from {
+                "cursor" => Ok(ConsoleCommand::SpawnFrom(SpawnFrom::Cursor)),
+                "stock" => Ok(ConsoleCommand::SpawnFrom(SpawnFrom::Stock)),
+                "origin" => Ok(ConsoleCommand::SpawnFrom(SpawnFrom::Origin)),
+                _ => Err(format!("unknown spawn start {from:?}")),
+            },
+            ("rule", [rule]) => match *rule {
+                "any" => Ok(ConsoleCommand::PlacementRule(PlacementRule::Any)),
+                "alternating" => Ok(ConsoleCommand::PlacementRule(
+                    PlacementRule::AlternatingDescending,
+                )),
+                "same-suit" => Ok(ConsoleCommand::PlacementRule(
+                    PlacementRule::SameSuitAscending,
+                )),
+                _ => Err(format!("unknown placement rule {rule:?}")),
+            },
+            ("inspect", [id]) => parse_id(id).map(ConsoleCommand::Inspect),
+            ("tag", [id, tag]) => parse_id(id).map(|id| ConsoleCommand::Tag(id, tag.to_string())),
+            ("untag", [id, tag]) => {
+                parse_id(id).map(|id| ConsoleCommand::Untag(id, tag.to_string()))
+            }
+            ("tagged", [tag]) => Ok(ConsoleCommand::Tagged(tag.to_string())),
+            ("spawn", _) => Err("usage: spawn <card>".to_owned()),
+            ("clear", _) => Err("usage: clear".to_owned()),
+            ("shuffle", _) => Err("usage: shuffle [seed]".to_owned()),
+            ("sort", _) => Err("usage: sort".to_owned()),
+            ("deal", _) => Err("usage: deal <count>".to_owned()),
+            ("deal-from", _) => Err("usage: deal-from <top|bottom>".to_owned()),
+            ("deal-facing", _) => Err("usage: deal-facing <up|down>".to_owned()),
+            ("recall-order", _) => {
+                Err("usage: recall-order <append|prepend|sorted|reverse>".to_owned())
+            }
+            ("complete", _) => Err("usage: complete <display|fly-off|fade-out|shrink>".to_owned()),
+            ("spawn-from", _) => Err("usage: spawn-from <cursor|stock|origin>".to_owned()),
+            ("rule", _) => Err("usage: rule <any|alternating|same-suit>".to_owned()),
+            ("tag", _) => Err("usage: tag <card id> <tag>".to_owned()),
+            ("untag", _) => Err("usage: untag <card id> <tag>".to_owned()),
+            ("tagged", _) => Err("usage: tagged <tag>".to_owned()),
+            ("pile", []) => Ok(ConsoleCommand::Pile),
+            ("compact", []) => Ok(ConsoleCommand::Compact),
+            ("save", []) => Ok(ConsoleCommand::Save),
+            ("load", []) => Ok(ConsoleCommand::Load),
+            ("inspect", _) => Err("usage: inspect <card id>".to_owned()),
+            ("pile", _) => Err("usage: pile".to_owned()),
+            ("compact", _) => Err("usage: compact".to_owned()),
+            ("save", _) => Err("usage: save".to_owned()),
+            ("load", _) => Err("usage: load".to_owned()),
+            _ => Err(format!("unknown command {name:?}")),
+        }
+    }
+}
+
+fn parse_id(id: &str) -> Result<CardId, String> {
+    id.parse()
+        .map(CardId)
+        .map_err(|_| format!("invalid card id {id:?}"))
+}
+
+/// Sent for each successfully parsed console line.
+#[derive(Event)]
+pub struct ConsoleInput(pub ConsoleCommand);
+
+/// A line for the console log, e.g. the result of a command.
+#[derive(Event)]
+pub struct ConsoleOutput(pub String);
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        self.log.push(line);
+        let excess = self.log.len().saturating_sub(CONSOLE_LOG_LINES);
+        self.log.drain(..excess);
+    }
+}
+
+#[derive(Component)]
+struct ConsoleText;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_event::<ConsoleInput>()
+            .add_event::<ConsoleOutput>()
+            .add_systems(Startup, spawn_console)
+            .add_systems(PreUpdate, console_input.after(InputSystem))
+            .add_systems(Update, (console_output, show_console).chain());
+    }
+}
+
+fn spawn_console(mut commands: Commands) {
+    commands.spawn((
+        ConsoleText,
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+/// Backtick opens and closes the console. While it is open it takes all
+/// keyboard input, so typing doesn't also trigger gameplay keys.
+fn console_input(
+    mut console: ResMut<Console>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut chars: EventReader<ReceivedCharacter>,
+    mut commands: EventWriter<ConsoleInput>,
+) {
+    let typed = chars.read().map(|typed| typed.char).collect::<Vec<_>>();
+
+    if keys.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        keys.reset_all();
+        return;
+    }
+
+    if !console.open {
+        return;
+    }
+
+    console
+        .input
+        .extend(typed.into_iter().filter(|c| !c.is_control() && *c != '`'));
+
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.print(format!("> {line}"));
+            match line.parse() {
+                Ok(command) => commands.send(ConsoleInput(command)),
+                Err(err) => console.print(err),
+            }
+        }
+    }
+
+    keys.reset_all();
+}
+
+fn console_output(mut console: ResMut<Console>, mut output: EventReader<ConsoleOutput>) {
+    for line in output.read() {
+        console.print(line.0.clone());
+    }
+}
+
+fn show_console(
+    console: Res<Console>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    for (mut text, mut visibility) in &mut query {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let mut contents = console.log.join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        contents.push_str(&format!("> {}_", console.input));
+        text.sections[0].value = contents;
+    }
+}
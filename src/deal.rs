@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use std::fmt;
+
+use crate::deck::{CardData, Deck};
+
+/// Cards dealt per hand.
+pub const HAND_SIZE: usize = 7;
+
+/// How a dealt hand is laid out on the grid.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DealPattern {
+    /// Every card in one cell, fanned.
+    #[default]
+    Fan,
+    /// One cell per card, left to right.
+    Row,
+    /// Rows of `cols` cells, filled left to right then downwards.
+    Grid { cols: usize },
+    /// Rows growing by one card, each centred under the one above.
+    Pyramid,
+}
+
+impl DealPattern {
+    pub const ALL: [DealPattern; 4] = [
+        DealPattern::Fan,
+        DealPattern::Row,
+        DealPattern::Grid { cols: HAND_SIZE },
+        DealPattern::Pyramid,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|pattern| *pattern == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Cell of each of `count` cards, in whole cells from the first card's cell.
+    /// Cells go right in +x and down in -y.
+    pub fn cells(self, count: usize) -> Vec<IVec2> {
+        match self {
+            DealPattern::Fan => vec![IVec2::ZERO; count],
+            DealPattern::Row => (0..count).map(|i| IVec2::new(i as i32, 0)).collect(),
+            DealPattern::Grid { cols } => {
+                let cols = cols.max(1);
+                (0..count)
+                    .map(|i| IVec2::new((i % cols) as i32, -((i / cols) as i32)))
+                    .collect()
+            }
+            // Cards of a row sit two cells apart so each row straddles the
+            // gaps of the row above.
+            DealPattern::Pyramid => (0..)
+                .flat_map(|row: i32| (0..=row).map(move |k| IVec2::new(2 * k - row, -row)))
+                .take(count)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for DealPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DealPattern::Fan => write!(f, "fan"),
+            DealPattern::Row => write!(f, "row"),
+            DealPattern::Grid { cols } => write!(f, "{cols}-column grid"),
+            DealPattern::Pyramid => write!(f, "pyramid"),
+        }
+    }
+}
+
+/// Which end of the deck dealt cards come from.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DealSource {
+    #[default]
+    Top,
+    Bottom,
+}
+
+impl DealSource {
+    pub fn draw(self, deck: &mut Deck) -> Option<CardData> {
+        match self {
+            DealSource::Top => deck.draw(),
+            DealSource::Bottom => deck.draw_bottom(),
+        }
+    }
+}
+
+/// Which side up a dealt hand, or a turn of the stock, lands.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DealFacing {
+    #[default]
+    FaceUp,
+    FaceDown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pyramid_rows_straddle_the_row_above() {
+        let cells = DealPattern::Pyramid.cells(10);
+        let expected = [
+            (0, 0),
+            (-1, -1),
+            (1, -1),
+            (-2, -2),
+            (0, -2),
+            (2, -2),
+            (-3, -3),
+            (-1, -3),
+            (1, -3),
+            (3, -3),
+        ]
+        .map(|(x, y)| IVec2::new(x, y));
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn partial_pyramid_fills_rows_left_to_right() {
+        let cells = DealPattern::Pyramid.cells(4);
+        assert_eq!(cells, DealPattern::Pyramid.cells(10)[..4]);
+        assert!(DealPattern::Pyramid.cells(0).is_empty());
+    }
+}
@@ -0,0 +1,619 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+pub const STANDARD_DECK_SIZE: usize = 52;
+
+/// Draws remembered for `RecallOrder::ReverseOfDraw`, oldest forgotten first.
+const DRAW_HISTORY_LEN: usize = 4 * STANDARD_DECK_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    pub fn symbol(self) -> char {
+        match self {
+            Suit::Clubs => 'C',
+            Suit::Diamonds => 'D',
+            Suit::Hearts => 'H',
+            Suit::Spades => 'S',
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|suit| symbol.eq_ignore_ascii_case(&suit.symbol().to_string()))
+    }
+
+    pub fn is_red(self) -> bool {
+        matches!(self, Suit::Diamonds | Suit::Hearts)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rank {
+    Ace = 1,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Rank::Ace => "A",
+            Rank::Two => "2",
+            Rank::Three => "3",
+            Rank::Four => "4",
+            Rank::Five => "5",
+            Rank::Six => "6",
+            Rank::Seven => "7",
+            Rank::Eight => "8",
+            Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        // "T" is accepted as shorthand so every token is two characters long.
+        if symbol.eq_ignore_ascii_case("T") {
+            return Some(Rank::Ten);
+        }
+
+        Self::ALL
+            .into_iter()
+            .find(|rank| symbol.eq_ignore_ascii_case(rank.symbol()))
+    }
+}
+
+/// The playing card a card entity represents.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CardData {
+    pub rank: Rank,
+    pub suit: Suit,
+    /// Which of the standard decks the card came from, counting from 0. Tells
+    /// duplicates apart without anything showing on the face.
+    pub deck: usize,
+}
+
+impl fmt::Display for CardData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank.symbol(), self.suit.symbol())
+    }
+}
+
+/// Parses rank + suit notation such as `AS`, `10H`, `TD` or `qc`.
+impl FromStr for CardData {
+    type Err = ParseError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let error = |reason| ParseError {
+            token: token.to_string(),
+            reason,
+        };
+
+        let Some((split, _)) = token.char_indices().last() else {
+            return Err(error(ParseErrorReason::Empty));
+        };
+        if split == 0 {
+            return Err(error(ParseErrorReason::TooShort));
+        }
+
+        let (rank, suit) = token.split_at(split);
+        let rank = Rank::from_symbol(rank).ok_or(error(ParseErrorReason::BadRank))?;
+        let suit = Suit::from_symbol(suit).ok_or(error(ParseErrorReason::BadSuit))?;
+
+        Ok(CardData {
+            rank,
+            suit,
+            deck: 0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorReason {
+    Empty,
+    TooShort,
+    BadRank,
+    BadSuit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub token: String,
+    pub reason: ParseErrorReason,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.reason {
+            ParseErrorReason::Empty => "empty card",
+            ParseErrorReason::TooShort => "expected a rank followed by a suit",
+            ParseErrorReason::BadRank => "unknown rank",
+            ParseErrorReason::BadSuit => "unknown suit",
+        };
+        write!(f, "invalid card {:?}: {reason}", self.token)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Cards still to be drawn. The last card is the top of the deck.
+///
+/// Built from `deck_count` standard decks, so duplicates are expected when
+/// `deck_count > 1`.
+#[derive(Resource, Clone, Debug)]
+pub struct Deck {
+    cards: Vec<CardData>,
+    deck_count: usize,
+    /// Recently drawn cards, oldest first, and whether each came off the bottom.
+    drawn: Vec<(CardData, bool)>,
+}
+
+impl Deck {
+    pub fn standard() -> Self {
+        Self::multi(1)
+    }
+
+    pub fn multi(n: usize) -> Self {
+        let cards = (0..n)
+            .flat_map(|deck| {
+                Suit::ALL.into_iter().flat_map(move |suit| {
+                    Rank::ALL
+                        .into_iter()
+                        .map(move |rank| CardData { rank, suit, deck })
+                })
+            })
+            .collect();
+
+        Self {
+            cards,
+            deck_count: n,
+            drawn: Vec::new(),
+        }
+    }
+
+    /// Builds a deck from whitespace separated cards, bottom first, e.g. `"AS KH 3D"`.
+    /// The second copy of a card comes from the second deck, and so on.
+    pub fn from_notation(notation: &str) -> Result<Self, ParseError> {
+        let mut copies = HashMap::<(Rank, Suit), usize>::new();
+        let cards = notation
+            .split_whitespace()
+            .map(|token| {
+                let card = token.parse::<CardData>()?;
+                let copy = copies.entry((card.rank, card.suit)).or_default();
+                let deck = *copy;
+                *copy += 1;
+                Ok(CardData { deck, ..card })
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        Ok(Self {
+            cards,
+            deck_count: 1,
+            drawn: Vec::new(),
+        })
+    }
+
+    pub fn deck_count(&self) -> usize {
+        self.deck_count
+    }
+
+    /// The same cards, checked by `validate` against `n` standard decks.
+    pub fn with_deck_count(self, n: usize) -> Self {
+        Self {
+            deck_count: n,
+            ..self
+        }
+    }
+
+    /// Checks that no card appears more often than `deck_count` standard decks
+    /// allow, e.g. five aces of spades in a single deck.
+    pub fn validate(&self) -> Result<(), DeckError> {
+        let mut copies = HashMap::<(Rank, Suit), usize>::new();
+        for card in &self.cards {
+            let count = copies.entry((card.rank, card.suit)).or_default();
+            *count += 1;
+
+            if *count > self.deck_count {
+                return Err(DeckError::TooManyCopies {
+                    card: *card,
+                    allowed: self.deck_count,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Cards from the bottom of the deck to the top.
+    pub fn iter(&self) -> impl Iterator<Item = &CardData> {
+        self.cards.iter()
+    }
+
+    pub fn last(&self) -> Option<&CardData> {
+        self.cards.last()
+    }
+
+    pub fn draw(&mut self) -> Option<CardData> {
+        let card = self.cards.pop()?;
+        self.remember_draw(card, false);
+        Some(card)
+    }
+
+    /// Takes the bottom card rather than the top one.
+    pub fn draw_bottom(&mut self) -> Option<CardData> {
+        if self.cards.is_empty() {
+            return None;
+        }
+        let card = self.cards.remove(0);
+        self.remember_draw(card, true);
+        Some(card)
+    }
+
+    fn remember_draw(&mut self, card: CardData, from_bottom: bool) {
+        if self.drawn.len() == DRAW_HISTORY_LEN {
+            self.drawn.remove(0);
+        }
+        self.drawn.push((card, from_bottom));
+    }
+
+    /// Returns `cards` to the deck, placed as `order` says.
+    pub fn recall(&mut self, mut cards: Vec<CardData>, order: RecallOrder) {
+        match order {
+            RecallOrder::Append => self.cards.extend(cards),
+            RecallOrder::Prepend => {
+                self.cards.splice(0..0, cards);
+            }
+            RecallOrder::Sorted => {
+                cards.sort_by_key(|card| (card.suit, card.rank));
+                self.cards.extend(cards);
+            }
+            RecallOrder::ReverseOfDraw => {
+                // Latest draws go back first, so the earliest drawn ends up
+                // nearest the end it came from. Cards drawn too long ago, or never
+                // drawn at all, go underneath everything.
+                let mut recalled = vec![false; self.drawn.len()];
+                let mut drawn_at = cards
+                    .into_iter()
+                    .map(|card| {
+                        let at = (0..self.drawn.len())
+                            .rev()
+                            .find(|at| !recalled[*at] && self.drawn[*at].0 == card);
+                        if let Some(at) = at {
+                            recalled[at] = true;
+                        }
+                        (at, card)
+                    })
+                    .collect::<Vec<_>>();
+                drawn_at.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+                for (at, card) in drawn_at {
+                    match at {
+                        Some(at) if !self.drawn[at].1 => self.cards.push(card),
+                        _ => self.cards.insert(0, card),
+                    }
+                }
+
+                let mut recalled = recalled.into_iter();
+                self.drawn.retain(|_| !recalled.next().unwrap_or(false));
+            }
+        }
+    }
+
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.cards.shuffle(rng);
+    }
+
+    /// Puts the deck back in new-deck order: bottom to top by suit, then by
+    /// rank within each suit. Duplicates from several decks end up side by side.
+    pub fn sort_standard(&mut self) {
+        self.cards
+            .sort_by_key(|card| (card.suit, card.rank, card.deck));
+    }
+
+    /// Splits the deck so that the `at` bottom-most cards move to the top.
+    ///
+    /// Cutting at `len() - at` afterwards restores the original order.
+    pub fn cut(&mut self, at: usize) -> Result<(), DeckError> {
+        if at > self.cards.len() {
+            return Err(DeckError::CutOutOfBounds {
+                at,
+                len: self.cards.len(),
+            });
+        }
+
+        self.cards.rotate_left(at);
+        Ok(())
+    }
+}
+
+/// Where cards returned to the deck go.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecallOrder {
+    /// On top, in the order given.
+    Append,
+    /// Underneath, in the order given.
+    Prepend,
+    /// On top, in new-deck order.
+    Sorted,
+    /// Back where they were drawn from, top or bottom, undoing the draws.
+    /// Cards with no record of being drawn go underneath.
+    #[default]
+    ReverseOfDraw,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeckError {
+    CutOutOfBounds { at: usize, len: usize },
+    TooManyCopies { card: CardData, allowed: usize },
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckError::CutOutOfBounds { at, len } => {
+                write!(f, "cannot cut a deck of {len} cards at {at}")
+            }
+            DeckError::TooManyCopies { card, allowed } => {
+                write!(f, "more than {allowed} copies of {card}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeckError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn sorting_puts_all_52_cards_in_new_deck_order() {
+        let mut deck = Deck::standard();
+        deck.shuffle(&mut StdRng::seed_from_u64(7));
+        deck.sort_standard();
+
+        let expected = Deck::from_notation(
+            "AC 2C 3C 4C 5C 6C 7C 8C 9C TC JC QC KC \
+             AD 2D 3D 4D 5D 6D 7D 8D 9D TD JD QD KD \
+             AH 2H 3H 4H 5H 6H 7H 8H 9H TH JH QH KH \
+             AS 2S 3S 4S 5S 6S 7S 8S 9S TS JS QS KS",
+        )
+        .unwrap();
+        assert_eq!(deck.len(), STANDARD_DECK_SIZE);
+        assert!(deck.iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn multi_holds_n_copies_of_every_card() {
+        for n in 1..=3 {
+            let deck = Deck::multi(n);
+            assert_eq!(deck.len(), STANDARD_DECK_SIZE * n);
+            assert_eq!(deck.deck_count(), n);
+
+            let mut copies = HashMap::<(Rank, Suit), usize>::new();
+            for card in deck.iter() {
+                *copies.entry((card.rank, card.suit)).or_default() += 1;
+            }
+            assert_eq!(copies.len(), STANDARD_DECK_SIZE);
+            assert!(copies.values().all(|count| *count == n));
+            for index in 0..n {
+                let from_deck = deck.iter().filter(|card| card.deck == index).count();
+                assert_eq!(from_deck, STANDARD_DECK_SIZE);
+            }
+            assert!(deck.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn multi_lays_out_each_deck_in_turn() {
+        let deck = Deck::multi(2);
+        let cards = deck.iter().copied().collect::<Vec<_>>();
+        let (first, second) = cards.split_at(STANDARD_DECK_SIZE);
+        assert_eq!(first, Deck::standard().iter().copied().collect::<Vec<_>>());
+        for (a, b) in first.iter().zip(second) {
+            assert_eq!(*b, CardData { deck: 1, ..*a });
+        }
+    }
+
+    #[test]
+    fn cutting_back_restores_the_order() {
+        let original = Deck::standard();
+        for at in [0, 1, 17, STANDARD_DECK_SIZE - 1, STANDARD_DECK_SIZE] {
+            let mut deck = original.clone();
+            deck.cut(at).unwrap();
+            assert_eq!(deck.len(), original.len());
+            if at % STANDARD_DECK_SIZE != 0 {
+                assert!(deck.iter().ne(original.iter()));
+            }
+
+            deck.cut(deck.len() - at).unwrap();
+            assert!(deck.iter().eq(original.iter()), "cut at {at}");
+        }
+    }
+
+    #[test]
+    fn cut_moves_the_bottom_cards_to_the_top() {
+        let mut deck = Deck::from_notation("AS 2S 3S 4S").unwrap();
+        deck.cut(1).unwrap();
+        assert!(deck
+            .iter()
+            .eq(Deck::from_notation("2S 3S 4S AS").unwrap().iter()));
+    }
+
+    #[test]
+    fn cut_past_the_end_is_an_error() {
+        let mut deck = Deck::standard();
+        assert_eq!(
+            deck.cut(STANDARD_DECK_SIZE + 1),
+            Err(DeckError::CutOutOfBounds {
+                at: STANDARD_DECK_SIZE + 1,
+                len: STANDARD_DECK_SIZE,
+            })
+        );
+        assert!(deck.iter().eq(Deck::standard().iter()));
+    }
+
+    fn card(rank: Rank, suit: Suit) -> CardData {
+        CardData {
+            rank,
+            suit,
+            deck: 0,
+        }
+    }
+
+    #[test]
+    fn recalled_cards_go_where_the_order_says() {
+        for (order, expected) in [
+            (RecallOrder::Append, "2S 3S KH 5S AS 4S"),
+            (RecallOrder::Prepend, "KH 5S AS 4S 2S 3S"),
+            (RecallOrder::Sorted, "2S 3S KH AS 4S 5S"),
+            // Each draw is undone at the end it came from, and the never drawn
+            // king goes underneath.
+            (RecallOrder::ReverseOfDraw, "KH AS 2S 3S 4S 5S"),
+        ] {
+            let mut deck = Deck::from_notation("AS 2S 3S 4S 5S").unwrap();
+            let five = deck.draw().unwrap();
+            let four = deck.draw().unwrap();
+            let ace = deck.draw_bottom().unwrap();
+
+            deck.recall(vec![card(Rank::King, Suit::Hearts), five, ace, four], order);
+            assert!(
+                deck.iter()
+                    .eq(Deck::from_notation(expected).unwrap().iter()),
+                "{order:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn notation_lists_cards_bottom_first() {
+        let deck = Deck::from_notation("AS KH 10d td 3C qc").unwrap();
+        assert_eq!(
+            deck.iter().copied().collect::<Vec<_>>(),
+            vec![
+                card(Rank::Ace, Suit::Spades),
+                card(Rank::King, Suit::Hearts),
+                card(Rank::Ten, Suit::Diamonds),
+                CardData {
+                    deck: 1,
+                    ..card(Rank::Ten, Suit::Diamonds)
+                },
+                card(Rank::Three, Suit::Clubs),
+                card(Rank::Queen, Suit::Clubs),
+            ]
+        );
+        assert_eq!(deck.last(), Some(&card(Rank::Queen, Suit::Clubs)));
+    }
+
+    #[test]
+    fn notation_ignores_extra_whitespace() {
+        let deck = Deck::from_notation("  AS\t\tKH\n\n 3D \r\n").unwrap();
+        assert!(deck
+            .iter()
+            .eq(Deck::from_notation("AS KH 3D").unwrap().iter()));
+        assert!(Deck::from_notation(" \n\t ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn notation_rejects_bad_tokens() {
+        for (notation, token, reason) in [
+            ("AS X", "X", ParseErrorReason::TooShort),
+            ("AS 1S", "1S", ParseErrorReason::BadRank),
+            ("AS 11S", "11S", ParseErrorReason::BadRank),
+            ("KH AX", "AX", ParseErrorReason::BadSuit),
+            ("KH A♠", "A♠", ParseErrorReason::BadSuit),
+        ] {
+            let error = Deck::from_notation(notation).unwrap_err();
+            assert_eq!(
+                error,
+                ParseError {
+                    token: token.to_owned(),
+                    reason,
+                },
+                "{notation:?}"
+            );
+        }
+        assert_eq!(
+            "".parse::<CardData>().unwrap_err().reason,
+            ParseErrorReason::Empty
+        );
+    }
+
+    #[test]
+    fn validate_allows_as_many_copies_as_decks() {
+        assert!(Deck::standard().validate().is_ok());
+        assert!(Deck::multi(2).validate().is_ok());
+        // Cards missing from a deck are fine, say from a partial notation.
+        assert!(Deck::from_notation("AS KH").unwrap().validate().is_ok());
+
+        let twice = Deck::from_notation("AS KH AS").unwrap();
+        assert_eq!(
+            twice.clone().validate(),
+            Err(DeckError::TooManyCopies {
+                card: CardData {
+                    deck: 1,
+                    ..card(Rank::Ace, Suit::Spades)
+                },
+                allowed: 1,
+            })
+        );
+        assert!(twice.with_deck_count(2).validate().is_ok());
+
+        let thrice = Deck::from_notation("AS AS AS").unwrap().with_deck_count(2);
+        assert!(matches!(
+            thrice.validate(),
+            Err(DeckError::TooManyCopies { allowed: 2, .. })
+        ));
+    }
+}
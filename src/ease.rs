@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use std::fmt;
+
+/// How far through a move a card is at each point of its duration. Every curve
+/// starts at exactly 0 and ends at exactly 1.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EaseCurve {
+    Linear,
+    /// Fast start, gentle landing.
+    #[default]
+    EaseOut,
+    EaseInOut,
+    /// Runs a little past the end and springs back.
+    Back,
+}
+
+impl EaseCurve {
+    pub const ALL: [EaseCurve; 4] = [
+        EaseCurve::Linear,
+        EaseCurve::EaseOut,
+        EaseCurve::EaseInOut,
+        EaseCurve::Back,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|curve| *curve == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Progress at `t`, clamped to 0..=1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            EaseCurve::Linear => t,
+            EaseCurve::EaseOut => 1.0 - (1.0 - t).powi(3),
+            EaseCurve::EaseInOut => t * t * (3.0 - 2.0 * t),
+            EaseCurve::Back => {
+                // The usual "ease out back" constant, about a 10% overshoot.
+                const OVERSHOOT: f32 = 1.701_58;
+                let u = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * u.powi(3) + OVERSHOOT * u.powi(2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for EaseCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EaseCurve::Linear => "linear",
+            EaseCurve::EaseOut => "ease out",
+            EaseCurve::EaseInOut => "ease in-out",
+            EaseCurve::Back => "back",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_curve_runs_from_0_to_1_and_clamps_outside() {
+        for curve in EaseCurve::ALL {
+            assert_eq!(curve.apply(0.0), 0.0, "{curve}");
+            assert!((curve.apply(1.0) - 1.0).abs() < 1e-6, "{curve}");
+            assert_eq!(curve.apply(-0.5), curve.apply(0.0), "{curve}");
+            assert_eq!(curve.apply(1.5), curve.apply(1.0), "{curve}");
+        }
+    }
+
+    #[test]
+    fn every_curve_but_back_only_moves_forward() {
+        let samples = (0..=100).map(|i| i as f32 / 100.0).collect::<Vec<_>>();
+        let rises = |curve: EaseCurve| {
+            samples
+                .windows(2)
+                .all(|pair| curve.apply(pair[1]) >= curve.apply(pair[0]))
+        };
+
+        for curve in [EaseCurve::Linear, EaseCurve::EaseOut, EaseCurve::EaseInOut] {
+            assert!(rises(curve), "{curve}");
+        }
+        // Back overshoots before settling, so it runs past 1 and comes back.
+        assert!(!rises(EaseCurve::Back));
+        assert!(samples.iter().any(|t| EaseCurve::Back.apply(*t) > 1.0));
+    }
+}
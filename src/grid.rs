@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::{CARD_IMAGE_SIZE, CARD_SIZE};
+
+/// Bounds used for "everything on this side" regions.
+const REGION_EXTENT: f32 = 100_000.0;
+
+/// Which part of a card lines up with its cell when snapped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    /// Card centred in the cell.
+    #[default]
+    Center,
+    /// Card's top-left corner on the cell's, so left edges sit flush.
+    TopLeft,
+    BottomLeft,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 3] = [Anchor::Center, Anchor::TopLeft, Anchor::BottomLeft];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|anchor| *anchor == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// A uniform grid of cells that cards snap to.
+#[derive(Clone, Copy, Debug)]
+pub struct GridConfig {
+    pub cell_size: Vec2,
+    /// A point on a cell corner; the grid extends from here in every direction.
+    pub origin: Vec2,
+    pub anchor: Anchor,
+    /// How big a resting card is, for `anchor` to line its edges up by.
+    pub card_size: Vec2,
+}
+
+impl GridConfig {
+    /// Cells with a little breathing room around a resting card.
+    pub fn standard() -> Self {
+        let card_size = CARD_IMAGE_SIZE.as_vec2() * CARD_SIZE.truncate();
+        Self {
+            cell_size: card_size * 1.2,
+            origin: Vec2::ZERO,
+            anchor: Anchor::Center,
+            card_size,
+        }
+    }
+
+    /// The cell containing `point`.
+    pub fn cell_rect(&self, point: Vec2) -> Rect {
+        let min = ((point - self.origin) / self.cell_size).floor() * self.cell_size + self.origin;
+        Rect::from_corners(min, min + self.cell_size)
+    }
+
+    /// Where a card's centre goes to sit in the cell containing `point`,
+    /// lined up by `anchor`.
+    pub fn snap(&self, point: Vec2) -> Vec2 {
+        let corner = self.cell_rect(point).min;
+        let card = self.card_size / 2.0;
+
+        match self.anchor {
+            Anchor::Center => corner + self.cell_size / 2.0,
+            Anchor::TopLeft => corner + Vec2::new(card.x, self.cell_size.y - card.y),
+            Anchor::BottomLeft => corner + card,
+        }
+    }
+}
+
+/// Splits the board into areas with their own grid. The first region containing
+/// a point wins, falling back to `fallback` outside all regions.
+#[derive(Resource)]
+pub struct GridRegions {
+    pub regions: Vec<(Rect, GridConfig)>,
+    pub fallback: GridConfig,
+}
+
+impl GridRegions {
+    /// Standard cells on the left half of the board, roomier ones on the right.
+    pub fn split_board() -> Self {
+        let standard = GridConfig::standard();
+        let roomy = GridConfig {
+            cell_size: standard.cell_size * 1.25,
+            ..standard
+        };
+
+        Self {
+            regions: vec![
+                (
+                    Rect::new(-REGION_EXTENT, -REGION_EXTENT, 0.0, REGION_EXTENT),
+                    standard,
+                ),
+                (
+                    Rect::new(0.0, -REGION_EXTENT, REGION_EXTENT, REGION_EXTENT),
+                    roomy,
+                ),
+            ],
+            fallback: standard,
+        }
+    }
+
+    /// Every grid, so cards of `size` line up in all of them.
+    pub fn set_card_size(&mut self, size: Vec2) {
+        self.fallback.card_size = size;
+        for (_, config) in &mut self.regions {
+            config.card_size = size;
+        }
+    }
+
+    pub fn config_at(&self, point: Vec2) -> &GridConfig {
+        self.regions
+            .iter()
+            .find(|(rect, _)| rect.contains(point))
+            .map_or(&self.fallback, |(_, config)| config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_anchor_lines_the_card_up_with_its_cell() {
+        let point = Vec2::new(130.0, -40.0);
+        for anchor in Anchor::ALL {
+            let grid = GridConfig {
+                anchor,
+                card_size: Vec2::new(60.0, 90.0),
+                ..GridConfig::standard()
+            };
+            let cell = grid.cell_rect(point);
+            let card = Rect::from_center_size(grid.snap(point), grid.card_size);
+
+            let (card_corner, cell_corner) = match anchor {
+                Anchor::Center => (card.center(), cell.center()),
+                Anchor::TopLeft => (
+                    Vec2::new(card.min.x, card.max.y),
+                    Vec2::new(cell.min.x, cell.max.y),
+                ),
+                Anchor::BottomLeft => (card.min, cell.min),
+            };
+            assert!(
+                card_corner.abs_diff_eq(cell_corner, 1e-3),
+                "{anchor:?}: {card_corner} != {cell_corner}"
+            );
+            let room = cell.inset(1e-3);
+            assert!(
+                room.contains(card.min) && room.contains(card.max),
+                "{anchor:?}"
+            );
+        }
+    }
+}
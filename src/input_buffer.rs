@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+/// How long a press waits for its system to be ready before it's dropped.
+const DEFAULT_WINDOW: f32 = 0.25;
+
+/// Remembers action key presses for a short while, so a press that lands while
+/// its system is busy (mid animation, say) still happens once it's free.
+pub struct InputBufferPlugin;
+
+impl Plugin for InputBufferPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBuffer::new(DEFAULT_WINDOW))
+            .add_systems(Update, record_actions.in_set(BufferedInput));
+    }
+}
+
+/// Systems that consume buffered actions run after this set, so they see the
+/// presses of the current frame.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BufferedInput;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Flip,
+    Shuffle,
+    Cut,
+}
+
+impl Action {
+    const BINDINGS: [(KeyCode, Action); 3] = [
+        (KeyCode::F, Action::Flip),
+        (KeyCode::R, Action::Shuffle),
+        (KeyCode::C, Action::Cut),
+    ];
+}
+
+#[derive(Resource)]
+pub struct InputBuffer {
+    /// Seconds a press stays available.
+    pub window: f32,
+    presses: Vec<(Action, f32)>,
+}
+
+impl InputBuffer {
+    pub fn new(window: f32) -> Self {
+        Self {
+            window,
+            presses: Vec::new(),
+        }
+    }
+
+    pub fn press(&mut self, action: Action, now: f32) {
+        self.presses.push((action, now));
+    }
+
+    /// Consumes the oldest unexpired press of `action`, if there is one.
+    pub fn take(&mut self, action: Action, now: f32) -> bool {
+        self.expire(now);
+
+        match self
+            .presses
+            .iter()
+            .position(|(pressed, _)| *pressed == action)
+        {
+            Some(i) => {
+                self.presses.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expire(&mut self, now: f32) {
+        let window = self.window;
+        self.presses.retain(|(_, at)| now - at <= window);
+    }
+}
+
+fn record_actions(keys: Res<Input<KeyCode>>, time: Res<Time>, mut buffer: ResMut<InputBuffer>) {
+    let now = time.elapsed_seconds();
+    buffer.expire(now);
+
+    for (key, action) in Action::BINDINGS {
+        if keys.just_pressed(key) {
+            buffer.press(action, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_press_is_taken_once() {
+        let mut buffer = InputBuffer::new(0.25);
+        buffer.press(Action::Flip, 1.0);
+
+        assert!(buffer.take(Action::Flip, 1.1));
+        assert!(!buffer.take(Action::Flip, 1.1));
+    }
+
+    #[test]
+    fn presses_expire_after_the_window() {
+        let mut buffer = InputBuffer::new(0.25);
+        buffer.press(Action::Flip, 1.0);
+        buffer.press(Action::Cut, 1.0);
+
+        assert!(buffer.take(Action::Cut, 1.25));
+        assert!(!buffer.take(Action::Flip, 1.3));
+    }
+
+    #[test]
+    fn the_oldest_press_is_taken_first() {
+        let mut buffer = InputBuffer::new(0.25);
+        buffer.press(Action::Shuffle, 1.0);
+        buffer.press(Action::Shuffle, 1.1);
+
+        assert!(buffer.take(Action::Shuffle, 1.2));
+        // Only the later press is left, and it is still inside the window.
+        assert!(buffer.take(Action::Shuffle, 1.3));
+        assert!(!buffer.take(Action::Shuffle, 1.3));
+    }
+
+    #[test]
+    fn taking_one_action_leaves_the_others() {
+        let mut buffer = InputBuffer::new(0.25);
+        buffer.press(Action::Cut, 1.0);
+
+        assert!(!buffer.take(Action::Flip, 1.0));
+        assert!(buffer.take(Action::Cut, 1.0));
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::{
+    card_id::CardId, deck::CardData, hit_test, Bounds, Interactive, Pile, Tags, WordCursor,
+};
+
+/// Alt + left click on a card shows its live component values in a side panel.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspected>()
+            .add_systems(Startup, spawn_inspector_panel)
+            .add_systems(Update, (inspect_card, show_inspector).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Inspected(pub Option<Entity>);
+
+pub fn inspect_modifier_pressed(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight])
+}
+
+#[derive(Component)]
+struct InspectorPanel;
+
+fn spawn_inspector_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            InspectorPanel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+pub fn inspect_card(
+    cards: Query<(Entity, &Bounds, &Transform), Interactive>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    world_cursor: Res<WordCursor>,
+    mut inspected: ResMut<Inspected>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let hit = cards
+        .iter()
+        .filter(|(_, bounds, transform)| hit_test(bounds.0, transform.rotation, world_cursor.0))
+        .max_by(|(_, _, a), (_, _, b)| a.translation.z.total_cmp(&b.translation.z))
+        .map(|(entity, _, _)| entity);
+
+    match hit {
+        Some(entity) if inspect_modifier_pressed(&keys) => inspected.0 = Some(entity),
+        Some(_) => {}
+        None => inspected.0 = None,
+    }
+}
+
+type InspectedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Bounds,
+        &'static Transform,
+        Option<&'static Pile>,
+        Option<&'static CardData>,
+        Option<&'static Tags>,
+        Option<&'static CardId>,
+    ),
+>;
+
+fn show_inspector(
+    inspected: Res<Inspected>,
+    cards: InspectedQuery,
+    mut panels: Query<(&mut Visibility, &Children), With<InspectorPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let details = inspected.0.and_then(|entity| {
+        let (bounds, transform, pile, card, tags, id) = cards.get(entity).ok()?;
+
+        Some(format!(
+            "Entity: {entity:?}\nId: {}\nCard: {}\nPile: {}\nBounds: {:.1?} .. {:.1?}\nz: {:.3}\nTags: {}",
+            id.map_or("-".to_string(), |id| id.0.to_string()),
+            card.map_or("-".to_string(), |card| card.to_string()),
+            pile.map_or("-".to_string(), |pile| format!("({}, {})", pile.0, pile.1)),
+            bounds.min,
+            bounds.max,
+            transform.translation.z,
+            tags.map_or("-".to_string(), |tags| {
+                tags.iter().cloned().collect::<Vec<_>>().join(", ")
+            }),
+        ))
+    });
+
+    for (mut visibility, children) in &mut panels {
+        *visibility = if details.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let Some(details) = &details else {
+            continue;
+        };
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = details.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+
+/// How a held key repeats: first after `delay` seconds, then every `interval`.
+#[derive(Resource)]
+pub struct KeyRepeat {
+    pub delay: f32,
+    pub interval: f32,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            delay: 0.4,
+            interval: 0.08,
+        }
+    }
+}
+
+/// Shared auto-repeat, so every held-key action repeats at the same cadence.
+/// Each system using it keeps its own hold times.
+#[derive(SystemParam)]
+pub struct RepeatingKeys<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    config: Res<'w, KeyRepeat>,
+    time: Res<'w, Time>,
+    held: Local<'s, HashMap<KeyCode, f32>>,
+}
+
+impl RepeatingKeys<'_, '_> {
+    /// True on the frame `key` is pressed, then once per repeat while it stays
+    /// held. Should be asked every frame for the keys it's used with.
+    pub fn repeating_just_pressed(&mut self, key: KeyCode) -> bool {
+        if self.keys.just_pressed(key) {
+            self.held.insert(key, 0.0);
+            return true;
+        }
+        if !self.keys.pressed(key) {
+            self.held.remove(&key);
+            return false;
+        }
+
+        let Some(held) = self.held.get_mut(&key) else {
+            return false;
+        };
+        let before = *held;
+        *held += self.time.delta_seconds();
+
+        // Repeats fall due at delay, delay + interval, delay + 2 * interval, ...
+        let delay = self.config.delay;
+        let interval = self.config.interval.max(f32::EPSILON);
+        let repeats = |held: f32| {
+            if held < delay {
+                0
+            } else {
+                ((held - delay) / interval) as u32 + 1
+            }
+        };
+        repeats(*held) > repeats(before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[derive(Resource, Default)]
+    struct Fired(Vec<usize>);
+
+    fn record(mut keys: RepeatingKeys, mut fired: ResMut<Fired>, mut frame: Local<usize>) {
+        *frame += 1;
+        if keys.repeating_just_pressed(KeyCode::Left) {
+            fired.0.push(*frame);
+        }
+    }
+
+    /// Runs a frame, then lets go of its presses the way the input plugin would.
+    fn step(app: &mut App) {
+        app.update();
+        app.world.resource_mut::<Input<KeyCode>>().clear();
+    }
+
+    #[test]
+    fn held_keys_repeat_after_the_delay_then_every_interval() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                125,
+            )))
+            .insert_resource(KeyRepeat {
+                delay: 0.5,
+                interval: 0.25,
+            })
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Fired>()
+            .add_systems(Update, record);
+        // The first update's clock doesn't move.
+        step(&mut app);
+
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Left);
+        for _ in 0..9 {
+            step(&mut app);
+        }
+        // Pressed on frame 2, then repeats half a second and every quarter after.
+        assert_eq!(app.world.resource::<Fired>().0, [2, 6, 8, 10]);
+
+        // Letting go starts the delay over.
+        app.world.resource_mut::<Fired>().0.clear();
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::Left);
+        step(&mut app);
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Left);
+        for _ in 0..5 {
+            step(&mut app);
+        }
+        assert_eq!(app.world.resource::<Fired>().0, [12, 16]);
+    }
+}
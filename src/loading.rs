@@ -0,0 +1,135 @@
+use bevy::{asset::LoadState, prelude::*};
+
+/// Whether required assets are ready. Gameplay systems only run in `Playing`.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    Loading,
+    Playing,
+    /// Some required asset couldn't be loaded; the error screen lists which.
+    Failed,
+}
+
+/// Assets the game can't run without, by path.
+#[derive(Resource, Default)]
+pub struct RequiredAssets(Vec<(&'static str, UntypedHandle)>);
+
+impl RequiredAssets {
+    /// Starts loading `path` and holds gameplay until it has loaded.
+    pub fn load<A: Asset>(&mut self, asset_server: &AssetServer, path: &'static str) -> Handle<A> {
+        let handle = asset_server.load(path);
+        self.0.push((path, handle.clone().untyped()));
+        handle
+    }
+}
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>()
+            .init_resource::<RequiredAssets>()
+            .add_systems(Startup, spawn_loading_screen)
+            .add_systems(Update, check_loading.run_if(in_state(AppState::Loading)))
+            .add_systems(OnExit(AppState::Loading), despawn_loading_screen);
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            LoadingScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Loading...",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn check_loading(
+    required: Res<RequiredAssets>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let states = required
+        .0
+        .iter()
+        .map(|(path, handle)| (*path, asset_server.get_load_state(handle.id())))
+        .collect::<Vec<_>>();
+
+    let failed = states
+        .iter()
+        .filter(|(_, state)| *state == Some(LoadState::Failed))
+        .map(|(path, _)| *path)
+        .collect::<Vec<_>>();
+
+    if !failed.is_empty() {
+        for path in &failed {
+            error!("failed to load required asset {path}");
+        }
+        spawn_error_screen(&mut commands, &failed);
+        next_state.set(AppState::Failed);
+    } else if states
+        .iter()
+        .all(|(_, state)| *state == Some(LoadState::Loaded))
+    {
+        next_state.set(AppState::Playing);
+    }
+}
+
+fn despawn_loading_screen(screens: Query<Entity, With<LoadingScreen>>, mut commands: Commands) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn spawn_error_screen(commands: &mut Commands, failed: &[&str]) {
+    let mut message = "Couldn't load these assets:\n".to_owned();
+    for path in failed {
+        message.push_str(&format!("\n  assets/{path}"));
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                message,
+                TextStyle {
+                    font_size: 30.0,
+                    color: Color::RED,
+                    ..default()
+                },
+            ));
+        });
+}
@@ -1,182 +1,5678 @@
 use bevy::{
     core_pipeline::clear_color::ClearColorConfig,
+    ecs::system::SystemParam,
+    input::mouse::{MouseScrollUnit, MouseWheel},
     math::{vec2, vec3},
     prelude::*,
-    render::view::RenderLayers,
-    utils::HashMap,
+    render::{
+        camera::CameraUpdateSystem,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::RenderLayers,
+    },
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+    window::WindowResized,
 };
+use card_id::{CardId, CardIdPlugin, CardIds};
+use clicks::{ClickTracker, Clicks, DoubleClickConfig};
+use console::{ConsoleCommand, ConsoleInput, ConsoleOutput, ConsolePlugin};
+use deal::{DealFacing, DealPattern, DealSource, HAND_SIZE};
+use deck::{CardData, Deck, Rank, RecallOrder, STANDARD_DECK_SIZE};
+use ease::EaseCurve;
+use grid::GridRegions;
+use input_buffer::{Action, BufferedInput, InputBuffer, InputBufferPlugin};
+use inspector::{inspect_modifier_pressed, Inspected, InspectorPlugin};
+use key_repeat::{KeyRepeat, RepeatingKeys};
+use loading::{AppState, LoadingPlugin, RequiredAssets};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rules::{foundation_run, PlacementRule};
+use save::{BoardSave, SaveConfig, SavedCard};
+use score::{ScoreEvent, ScorePlugin};
+use screenshot::ScreenshotPlugin;
+use sounds::SoundsPlugin;
+use stats::StatsPlugin;
+use std::f32::consts::PI;
+use toast::{Toast, ToastPlugin};
+use tutorial::{TutorialKeys, TutorialPlugin};
+
+mod card_id;
+mod clicks;
+mod console;
+mod deal;
+mod deck;
+mod ease;
+mod grid;
+mod input_buffer;
+mod inspector;
+mod key_repeat;
+mod loading;
+mod rules;
+mod save;
+mod score;
+mod screenshot;
+mod sounds;
+mod stats;
+#[cfg(test)]
+mod tests;
+mod toast;
+mod tutorial;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
-        .insert_resource(WordCursor(Vec2::ZERO))
+        .add_plugins(BoardPlugin)
+        .add_plugins((
+            TutorialPlugin,
+            ToastPlugin,
+            InspectorPlugin,
+            ScreenshotPlugin,
+            LoadingPlugin,
+            ConsolePlugin,
+            StatsPlugin,
+            InputBufferPlugin,
+            CardIdPlugin,
+            ScorePlugin,
+            SoundsPlugin,
+        ))
         .add_systems(Startup, setup)
+        .add_systems(OnEnter(AppState::Playing), deal_starting_tableau)
         //.add_plugins(bevy_editor_pls::EditorPlugin::default())
         .add_systems(
             Update,
             (
-                update_cursor,
+                (read_pointer, touch_gesture, update_cursor).chain(),
                 update_bounds,
-                drag_selected,
-                finish_drag_selected,
-                non_selected,
-                select_card,
-                create_card,
-                show_piles,
-                align_placed,
-                animate_sprite,
-                move_player_system,
+                nudge_hovered,
+                (
+                    (select_card.after(TutorialKeys), deselect_on_empty_tap),
+                    box_select,
+                    drag_selected,
+                    discard_dropped,
+                    flick_waste.after(drag_selected),
+                )
+                    .run_if(dragging_allowed),
+                (
+                    finish_drag_selected.before(index_piles),
+                    non_selected,
+                    align_placed.after(finish_drag_selected),
+                    start_land_bounce,
+                    land_bounce,
+                    wiggle,
+                    settle_compacted,
+                    auto_stack_on_contact,
+                )
+                    .run_if(not(paused)),
+                (
+                    resolve_right_click,
+                    create_card,
+                    cancel_drag,
+                    open_context_menu,
+                )
+                    .chain(),
+                recall_discard,
+                hover_cards,
+                lift_hovered.run_if(not(fan_hover_enabled)),
+                fan_hover.run_if(fan_hover_enabled).run_if(not(paused)),
+                index_piles,
+                (
+                    show_piles,
+                    pile_validity,
+                    collapse_single_piles,
+                    sort_foundation_piles,
+                    sink_flicked.before(ysort),
+                    answer_pile_queries,
+                    compact_piles,
+                    detect_completed_sets.after(sort_foundation_piles),
+                    report_score_events.after(detect_completed_sets),
+                    auto_flip,
+                )
+                    .after(index_piles),
+                ysort.after(index_piles).after(move_player_system),
+                show_pile_slots.after(index_piles),
+                (
+                    show_cursor.after(update_cursor),
+                    show_box_select.after(box_select),
+                    show_drop_ghost,
+                    preview_pickup,
+                    outline_kept_selection,
+                    show_pile_validity.after(pile_validity),
+                    show_discard_zone,
+                    show_foundations,
+                )
+                    .run_if(resource_equals(DebugGizmos(true))),
+                (
+                    flip_card,
+                    flip_hovered.after(BufferedInput),
+                    reveal_on_select,
+                ),
+                recover_offboard.run_if(not(paused)),
+                flip_animation
+                    .after(drag_selected)
+                    .after(finish_drag_selected)
+                    .after(non_selected)
+                    .after(land_bounce)
+                    .run_if(not(paused)),
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        // Despawn after Update's commands are applied, so nothing queued this
+        // frame targets a card that no longer exists.
+        .add_systems(
+            PostUpdate,
+            (
+                despawn_cards,
+                apply_card_layer,
+                apply_contrast_textures,
+                anchor_discard_zone
+                    .after(CameraUpdateSystem)
+                    .after(TransformSystem::TransformPropagate),
             ),
         )
+        .add_systems(
+            Update,
+            (
+                reshuffle.after(BufferedInput),
+                shuffle_animation.before(TutorialKeys).run_if(not(paused)),
+                cut_deck.after(BufferedInput),
+                cut_animation.run_if(not(paused)),
+                load_deck,
+                show_stock,
+                peek_deck,
+                reskin_backs,
+                toggle_back_tints,
+                apply_back_tint,
+                (deal_key, auto_deal.run_if(not(paused))),
+                deal_hand
+                    .after(deal_key)
+                    .after(auto_deal)
+                    .after(run_console_commands)
+                    .after(configure_deal),
+                (
+                    run_console_commands,
+                    configure_deal,
+                    configure_placement,
+                    run_tag_commands,
+                    save_board,
+                    load_board,
+                ),
+                (cycle_deal_pattern, print_pile_contents),
+                reveal_all,
+                (
+                    clear_completed_set,
+                    animate_display_row.after(clear_completed_set),
+                    animate_completing.run_if(not(paused)),
+                ),
+                (
+                    fade_disappearing.run_if(not(paused)),
+                    (toggle_motion_trails, spawn_trail_ghosts, fade_trail_ghosts).chain(),
+                ),
+                click_stock,
+                (deal_animation, start_queued_deals)
+                    .chain()
+                    .run_if(not(paused)),
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                (animate_sprite, move_player_system).run_if(not(paused)),
+                adjust_animation_speed,
+                face_player,
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                rotate_board_view,
+                pan_camera,
+                zoom_camera,
+                reset_camera,
+                cycle_camera_focus,
+                follow_focus
+                    .after(cycle_camera_focus)
+                    .after(pan_camera)
+                    .after(reset_camera),
+                clamp_camera
+                    .after(pan_camera)
+                    .after(zoom_camera)
+                    .after(reset_camera)
+                    .after(follow_focus),
+                toggle_debug_gizmos,
+                toggle_pause,
+                show_pause_banner,
+                (toggle_settle_mode, cycle_settle_ease),
+                toggle_pickup_mode,
+                toggle_recover_offboard,
+                toggle_auto_collapse,
+                (toggle_sticky_drag, toggle_persist_selection),
+                toggle_snap_mode,
+                (toggle_fan_hover, toggle_card_layer, toggle_auto_stack),
+                (toggle_high_contrast, apply_high_contrast).chain(),
+                (
+                    toggle_show_grid,
+                    (cycle_grid_anchor, fit_grid_to_cards, refit_piles_to_grid).chain(),
+                    draw_grid,
+                ),
+                (toggle_cursor_cell, highlight_cursor_cell),
+            )
+                .run_if(in_state(AppState::Playing)),
+        )
         .run();
 }
 
-fn update_cursor(
-    camera_query: Query<(&Camera, &GlobalTransform, With<CardsCamera>)>,
-    windows: Query<&Window>,
-    mut world_cursor: ResMut<WordCursor>,
-    mut gizmos: Gizmos,
+/// Every resource and event the board systems share, at the game's defaults.
+/// Kept apart from the systems themselves so an app can be built around just
+/// the ones it needs.
+struct BoardPlugin;
+
+impl Plugin for BoardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WordCursor(Vec2::ZERO))
+            .init_resource::<PointerState>()
+            .init_resource::<KeyRepeat>()
+            .insert_resource(TouchGesture {
+                tap_time: 0.2,
+                tap_dist: 12.0,
+            })
+            .insert_resource(CursorSmoothing { factor: 0.0 })
+            .init_resource::<ShuffleAnimation>()
+            .insert_resource(AnimationSpeed(1.0))
+            .init_resource::<SpawnFrom>()
+            .insert_resource(Deck::standard())
+            .insert_resource(DebugGizmos(true))
+            .init_resource::<PileIndex>()
+            .init_resource::<BackTheme>()
+            .insert_resource(Paused(false))
+            .insert_resource(DragWhilePaused(true))
+            // Placed in the bottom right corner by anchor_discard_zone.
+            .insert_resource(DiscardZone(Rect::default()))
+            .insert_resource(FlingDiscard {
+                speed_threshold: 1500.0,
+            })
+            .insert_resource(FlickWaste {
+                speed_threshold: 900.0,
+            })
+            .init_resource::<SettleMode>()
+            .init_resource::<SettleEase>()
+            .insert_resource(PeekCount(0))
+            .insert_resource(SnapRadius(80.0))
+            .insert_resource(LandBounceConfig {
+                magnitude: 0.15,
+                duration: 0.3,
+            })
+            .insert_resource(SpringSettle {
+                stiffness: 300.0,
+                damping: 20.0,
+            })
+            .insert_resource(FollowSpring {
+                stiffness: 400.0,
+                damping: 40.0,
+            })
+            .add_event::<Reshuffled>()
+            .add_event::<DeckCut>()
+            .init_resource::<CutAnimation>()
+            .add_event::<DespawnCard>()
+            .add_event::<CardSpawned>()
+            .add_event::<CardPickedUp>()
+            .add_event::<CardDropped>()
+            .insert_resource(MaxCards(500))
+            .init_resource::<SpawnTemplate>()
+            .insert_resource(CycleOnSpawn(false))
+            .insert_resource(SpawnKey(None))
+            .insert_resource(CardLayer(INTERACTIVE_LAYER))
+            .insert_resource(Foundations::top_right(&GridRegions::split_board()))
+            .insert_resource(GridRegions::split_board())
+            .init_resource::<LastDiscard>()
+            .init_resource::<SaveConfig>()
+            .init_resource::<DoubleClickConfig>()
+            .init_resource::<ClickTracker>()
+            .init_resource::<PlacementRule>()
+            .init_resource::<PileValidity>()
+            .init_resource::<BoardRotation>()
+            .init_resource::<PickupMode>()
+            .init_resource::<DealPattern>()
+            .init_resource::<DealSource>()
+            .init_resource::<DealFacing>()
+            .init_resource::<RecallOrder>()
+            .add_event::<DealHand>()
+            .add_event::<RightClicked>()
+            .add_event::<QueryPile>()
+            .add_event::<CompactPiles>()
+            .add_event::<PileContents>()
+            .insert_resource(RightClickBindings {
+                carrying: RightClickAction::CancelDrag,
+                over_card: RightClickAction::ContextMenu,
+                empty_space: RightClickAction::SpawnCard,
+            })
+            .add_event::<SetCompleted>()
+            .init_resource::<CompleteAnimation>()
+            .insert_resource(DisplayRow {
+                origin: vec2(-560.0, -300.0),
+                spacing: 40.0,
+            })
+            .insert_resource(BoardBounds(Rect::from_center_size(Vec2::ZERO, BOARD_SIZE)))
+            .insert_resource(CameraBounds(
+                Rect::from_center_size(Vec2::ZERO, BOARD_SIZE).inset(CAMERA_MARGIN),
+            ))
+            .insert_resource(RecoverOffboard(true))
+            .insert_resource(AutoCollapseSingles(false))
+            .insert_resource(AutoStackOnContact(false))
+            .insert_resource(StickyDrag(false))
+            .insert_resource(PersistSelection(false))
+            .insert_resource(TapToDeselect(true))
+            .insert_resource(PileMarkers {
+                tableau: true,
+                foundation: true,
+            })
+            .insert_resource(MotionTrails {
+                enabled: false,
+                speed_threshold: 1500.0,
+                interval: 0.03,
+            })
+            .insert_resource(DealTiming {
+                stagger: 0.08,
+                flight: 0.35,
+            })
+            .insert_resource(MaxConcurrentDeals(8))
+            .insert_resource(HighContrast(false))
+            .insert_resource(StockDeal { per_click: 1 })
+            .insert_resource(MaxPileExtent(120.0))
+            .insert_resource(DiscardDelay(0.3))
+            .insert_resource(CameraFocus::new(0.6))
+            .insert_resource(CameraOffset {
+                offset: Vec2::ZERO,
+                lead_with_facing: false,
+            })
+            .insert_resource(ShowGrid(false))
+            .insert_resource(ShowCursorCell(false))
+            .insert_resource(DealTableauOnStart(true))
+            .insert_resource(RevealOnSelect(false))
+            .insert_resource(AutoDeal {
+                enabled: false,
+                delay: 2.0,
+            })
+            .insert_resource(FanHover(false))
+            .insert_resource(MaxSelection(100))
+            .init_resource::<BoxSelect>()
+            .init_resource::<NextDragOrder>()
+            .init_resource::<SnapMode>()
+            .init_resource::<AutoFlipOnDrop>()
+            .insert_resource(FlipInput {
+                double_click: true,
+                key: true,
+            })
+            .insert_resource(IdleReturn {
+                timeout: 30.0,
+                spawn: Vec2::ZERO,
+            })
+            .init_resource::<BackTints>();
+    }
+}
+
+/// Low-pass filter on the world cursor for jittery mice. Each frame the cursor
+/// moves `1 - factor` of the way to the raw point: 0 is raw, closer to 1 is
+/// smoother but lags more.
+#[derive(Resource)]
+struct CursorSmoothing {
+    factor: f32,
+}
+
+/// The raw pointer in world space, before smoothing. `read_pointer` fills it
+/// from the window; an app without a window (a headless test, say) sets it
+/// directly, and everything downstream only ever sees `WordCursor`.
+#[derive(Resource, Default)]
+struct PointerState {
+    /// `None` until the pointer has been over the window.
+    world: Option<Vec2>,
+}
+
+fn read_pointer(
+    camera_query: Query<(&Camera, &GlobalTransform, With<CardsCamera>)>,
+    windows: Query<&Window>,
+    mut pointer: ResMut<PointerState>,
+) {
+    let (Ok((camera, camera_transform, _)), Ok(window)) =
+        (camera_query.get_single(), windows.get_single())
+    else {
+        return;
+    };
+
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    // Calculate a world position based on the cursor's position.
+    if let Some(point) = camera.viewport_to_world_2d(camera_transform, cursor_position) {
+        pointer.world = Some(point);
+    }
+}
+
+/// How a touch is told apart as a tap, which flips the card under it, or a
+/// drag, which carries it.
+#[derive(Resource)]
+struct TouchGesture {
+    /// Seconds a still touch lasts before it counts as held. A held touch
+    /// still flips on release, but no longer turns into a drag.
+    tap_time: f32,
+    /// Screen pixels the finger must travel before a touch becomes a drag.
+    tap_dist: f32,
+}
+
+/// The touches `touch_gesture` reads, and the one finger it is following.
+#[derive(SystemParam)]
+struct TouchState<'w, 's> {
+    touches: Res<'w, Touches>,
+    gesture: Res<'w, TouchGesture>,
+    time: Res<'w, Time>,
+    tracked: Local<'s, Option<TrackedTouch>>,
+}
+
+/// The one touch `touch_gesture` follows; other fingers are ignored.
+struct TrackedTouch {
+    id: u64,
+    /// World point the finger first came down on.
+    start: Vec2,
+    started_at: f32,
+    dragging: bool,
+    /// Stayed still for `tap_time`.
+    held: bool,
+    /// Went further than `tap_dist` from where it started.
+    strayed: bool,
+}
+
+/// Drives the pointer from the first finger down and classifies the touch. It
+/// becomes a drag as soon as the finger moves far enough, however quickly, and
+/// is a tap whenever it never moves that far. A long press that never moves is
+/// held rather than dragged, so it flips on release instead of leaving a card
+/// stuck to an unmoving finger.
+fn touch_gesture(
+    state: TouchState,
+    camera_query: Query<(&Camera, &GlobalTransform, With<CardsCamera>)>,
+    mut pickup: Pickup,
+    faces: Query<&Face, Without<FlipAnim>>,
+    mut pointer: ResMut<PointerState>,
+    mut commands: Commands,
+) {
+    let Ok((camera, camera_transform, _)) = camera_query.get_single() else {
+        return;
+    };
+    let to_world = |position| camera.viewport_to_world_2d(camera_transform, position);
+    let TouchState {
+        touches,
+        gesture,
+        time,
+        mut tracked,
+    } = state;
+    let now = time.elapsed_seconds();
+
+    if tracked.is_none() {
+        if let Some(touch) = touches.iter_just_pressed().next() {
+            if let Some(start) = to_world(touch.position()) {
+                *tracked = Some(TrackedTouch {
+                    id: touch.id(),
+                    start,
+                    started_at: now,
+                    dragging: false,
+                    held: false,
+                    strayed: false,
+                });
+                pointer.world = Some(start);
+            }
+        }
+    }
+
+    let Some(touch) = tracked.as_mut() else {
+        return;
+    };
+
+    if let Some(pressed) = touches.get_pressed(touch.id) {
+        if let Some(point) = to_world(pressed.position()) {
+            pointer.world = Some(point);
+        }
+
+        let moved = pressed.distance().length() > gesture.tap_dist;
+        touch.strayed |= moved;
+        if !touch.strayed && now - touch.started_at > gesture.tap_time {
+            touch.held = true;
+        }
+        if !touch.dragging && !touch.held && moved {
+            touch.dragging = true;
+
+            let targets = pickup.targets(touch.start);
+            let carried = pickup.carried.iter().collect::<Vec<_>>();
+            change_selection(
+                SelectionModifier::Replace,
+                &targets,
+                &carried,
+                &mut pickup,
+                &mut commands,
+            );
+        }
+        return;
+    }
+
+    if !(touches.just_released(touch.id) || touches.just_canceled(touch.id)) {
+        return;
+    }
+
+    if touch.dragging {
+        for entity in &pickup.carried {
+            commands.entity(entity).remove::<Selected>();
+        }
+    } else if touches.just_released(touch.id) && !touch.strayed {
+        let tapped = pickup
+            .cards
+            .iter()
+            .filter(|(_, bounds, transform, _)| hit_test(bounds.0, transform.rotation, touch.start))
+            .max_by(|(_, _, a, _), (_, _, b, _)| a.translation.z.total_cmp(&b.translation.z))
+            .map(|(entity, ..)| entity);
+
+        if let Some((entity, face)) =
+            tapped.and_then(|entity| Some((entity, faces.get(entity).ok()?)))
+        {
+            request_flip(entity, *face, &mut commands);
+        }
+    }
+    *tracked = None;
+}
+
+fn update_cursor(
+    pointer: Res<PointerState>,
+    smoothing: Res<CursorSmoothing>,
+    mut world_cursor: ResMut<WordCursor>,
+) {
+    let Some(point) = pointer.world else {
+        return;
+    };
+
+    // Hit-testing and dragging all read `WordCursor`, so they agree on the
+    // smoothed position.
+    let factor = smoothing.factor.clamp(0.0, 1.0);
+    world_cursor.0 = point.lerp(world_cursor.0, factor);
+}
+
+fn show_cursor(world_cursor: Res<WordCursor>, mut gizmos: Gizmos) {
+    gizmos.circle_2d(world_cursor.0, 10., Color::WHITE);
+}
+
+/// Whether the board view is turned around to face the player across the table.
+#[derive(Resource, Default)]
+struct BoardRotation {
+    flipped: bool,
+}
+
+const BOARD_ROTATION_SPEED: f32 = 8.0;
+
+/// V turns the cards camera 180°. The cursor is mapped through the camera's
+/// transform and snapping happens in world space, so picking, dragging and
+/// grid alignment all keep working from the rotated viewpoint.
+fn rotate_board_view(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut rotation: ResMut<BoardRotation>,
+    mut cameras: Query<&mut Transform, With<CardsCamera>>,
+) {
+    if keys.just_pressed(KeyCode::V) {
+        rotation.flipped = !rotation.flipped;
+    }
+
+    let angle = if rotation.flipped { PI } else { 0.0 };
+    let target = Quat::from_rotation_z(angle);
+
+    for mut transform in &mut cameras {
+        transform.rotation = if transform.rotation.angle_between(target) < 0.001 {
+            target
+        } else {
+            let t = (BOARD_ROTATION_SPEED * time.delta_seconds()).min(1.0);
+            transform.rotation.slerp(target, t)
+        };
+    }
+}
+
+const BOARD_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+/// How far past the board the view may pan.
+const CAMERA_MARGIN: f32 = 200.0;
+const CAMERA_PAN_SPEED: f32 = 600.0;
+const MIN_ZOOM: f32 = 0.5;
+/// Zoomed all the way out the view is larger than `CameraBounds`, so the
+/// camera is held on the centre of the board.
+const MAX_ZOOM: f32 = 2.0;
+
+/// The area the cards camera's view has to stay within.
+#[derive(Resource, Deref)]
+struct CameraBounds(Rect);
+
+/// Arrow keys pan the cards camera, in screen directions even when the view is rotated.
+fn pan_camera(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut focus: ResMut<CameraFocus>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<CardsCamera>>,
+) {
+    // Shift + arrows nudge cards instead.
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        direction.x += 1.0;
+    }
+    if keys.pressed(KeyCode::Up) {
+        direction.y += 1.0;
+    }
+    if keys.pressed(KeyCode::Down) {
+        direction.y -= 1.0;
+    }
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+    // Panning by hand stops following whatever was focused.
+    focus.set_target(FocusTarget::Free);
+
+    for (mut transform, projection) in &mut cameras {
+        let step =
+            direction.normalize() * CAMERA_PAN_SPEED * projection.scale * time.delta_seconds();
+        let step = transform.rotation * step.extend(0.0);
+        transform.translation += step;
+    }
+}
+
+/// The mouse wheel zooms the cards camera between `MIN_ZOOM` and `MAX_ZOOM`.
+fn zoom_camera(
+    mut wheel: EventReader<MouseWheel>,
+    mut cameras: Query<&mut OrthographicProjection, With<CardsCamera>>,
+) {
+    let scroll = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.0,
+        })
+        .sum::<f32>();
+
+    if scroll == 0.0 {
+        return;
+    }
+
+    for mut projection in &mut cameras {
+        projection.scale = (projection.scale * (1.0 - scroll * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// Where the cards camera starts, captured in `setup` so Home returns there.
+#[derive(Resource)]
+struct CameraDefaults {
+    translation: Vec2,
+    scale: f32,
+}
+
+const CAMERA_RESET_SPEED: f32 = 6.0;
+
+/// Home eases the cards camera back to its `CameraDefaults`. The cursor keeps
+/// mapping through the transform each frame was drawn with, so picking stays
+/// accurate while it moves.
+fn reset_camera(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    defaults: Res<CameraDefaults>,
+    mut focus: ResMut<CameraFocus>,
+    mut resetting: Local<bool>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<CardsCamera>>,
+) {
+    if keys.just_pressed(KeyCode::Home) {
+        focus.set_target(FocusTarget::Free);
+        *resetting = true;
+    }
+
+    if !*resetting {
+        return;
+    }
+
+    let t = (CAMERA_RESET_SPEED * time.delta_seconds()).min(1.0);
+
+    for (mut transform, mut projection) in &mut cameras {
+        let mut pos = transform
+            .translation
+            .truncate()
+            .lerp(defaults.translation, t);
+        let mut scale = projection.scale + (defaults.scale - projection.scale) * t;
+
+        if pos.distance(defaults.translation) < 0.5 && (scale - defaults.scale).abs() < 0.001 {
+            pos = defaults.translation;
+            scale = defaults.scale;
+            *resetting = false;
+        }
+
+        transform.translation = pos.extend(transform.translation.z);
+        projection.scale = scale;
+    }
+}
+
+/// Keeps the visible area inside `CameraBounds` at the current zoom. On an
+/// axis where the view is wider than the bounds, the camera is centred instead.
+fn clamp_camera(
+    bounds: Res<CameraBounds>,
+    windows: Query<&Window>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<CardsCamera>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    for (mut transform, projection) in &mut cameras {
+        let half_view = vec2(window.width(), window.height()) / 2.0 * projection.scale;
+        let min = bounds.min + half_view;
+        let max = bounds.max - half_view;
+        let center = bounds.center();
+
+        let pos = transform.translation.truncate();
+        let clamped = vec2(
+            if min.x <= max.x {
+                pos.x.clamp(min.x, max.x)
+            } else {
+                center.x
+            },
+            if min.y <= max.y {
+                pos.y.clamp(min.y, max.y)
+            } else {
+                center.y
+            },
+        );
+
+        if clamped != pos {
+            transform.translation = clamped.extend(transform.translation.z);
+        }
+    }
+}
+
+/// What the cards camera keeps centred on.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FocusTarget {
+    /// Nothing; the camera stays where it is put.
+    Free,
+    Entity(Entity),
+    Point(Vec2),
+}
+
+/// The camera's focus. A new target is blended to over `transition` seconds,
+/// after which `follow_focus` keeps tracking it.
+#[derive(Resource)]
+struct CameraFocus {
+    target: FocusTarget,
+    transition: f32,
+    blend: Option<FocusBlend>,
+}
+
+/// Progress of the move to a new target. `from` is filled in from the
+/// camera on the first frame of the blend.
+#[derive(Clone, Copy)]
+struct FocusBlend {
+    from: Option<Vec2>,
+    elapsed: f32,
+}
+
+impl CameraFocus {
+    fn new(transition: f32) -> Self {
+        Self {
+            target: FocusTarget::Free,
+            transition,
+            blend: None,
+        }
+    }
+
+    fn set_target(&mut self, target: FocusTarget) {
+        if target == self.target {
+            return;
+        }
+
+        self.target = target;
+        self.blend = Some(FocusBlend {
+            from: None,
+            elapsed: 0.0,
+        });
+    }
+}
+
+/// How quickly the camera catches up with a focused target once it's reached.
+const CAMERA_FOLLOW_SPEED: f32 = 8.0;
+
+/// J moves the focus from nothing, to the player, to the completed sets.
+fn cycle_camera_focus(
+    keys: Res<Input<KeyCode>>,
+    players: Query<Entity, With<Player>>,
+    row: Res<DisplayRow>,
+    mut focus: ResMut<CameraFocus>,
+) {
+    if !keys.just_pressed(KeyCode::J) {
+        return;
+    }
+
+    let player = players.iter().next();
+    let next = match (focus.target, player) {
+        (FocusTarget::Free, Some(player)) => FocusTarget::Entity(player),
+        (FocusTarget::Free, None) | (FocusTarget::Entity(_), _) => FocusTarget::Point(row.origin),
+        (FocusTarget::Point(_), _) => FocusTarget::Free,
+    };
+    focus.set_target(next);
+}
+
+/// Where the camera sits relative to a focused entity, e.g. above the player so
+/// more of the board shows ahead. With `lead_with_facing`, `offset.x` points
+/// the way the entity last moved.
+#[derive(Resource)]
+struct CameraOffset {
+    offset: Vec2,
+    lead_with_facing: bool,
+}
+
+impl CameraOffset {
+    fn for_facing(&self, facing: Option<&Facing>) -> Vec2 {
+        match facing {
+            Some(facing) if self.lead_with_facing && facing.x < 0.0 => {
+                vec2(-self.offset.x, self.offset.y)
+            }
+            _ => self.offset,
+        }
+    }
+}
+
+fn follow_focus(
+    time: Res<Time>,
+    targets: Query<(&GlobalTransform, Option<&Facing>)>,
+    offset: Res<CameraOffset>,
+    mut focus: ResMut<CameraFocus>,
+    mut cameras: Query<&mut Transform, With<CardsCamera>>,
+) {
+    let target = match focus.target {
+        FocusTarget::Free => {
+            focus.blend = None;
+            return;
+        }
+        FocusTarget::Point(point) => point,
+        FocusTarget::Entity(entity) => match targets.get(entity) {
+            Ok((transform, facing)) => {
+                transform.translation().truncate() + offset.for_facing(facing)
+            }
+            // The target is gone; leave the camera where it is.
+            Err(_) => {
+                focus.target = FocusTarget::Free;
+                focus.blend = None;
+                return;
+            }
+        },
+    };
+
+    let transition = focus.transition.max(f32::EPSILON);
+    let dt = time.delta_seconds();
+
+    for mut transform in &mut cameras {
+        let pos = transform.translation.truncate();
+
+        let next = match &mut focus.blend {
+            Some(blend) => {
+                let from = *blend.from.get_or_insert(pos);
+                blend.elapsed += dt;
+                let t = (blend.elapsed / transition).min(1.0);
+                // Ease in and out so the move neither jerks away nor bumps to a stop.
+                from.lerp(target, t * t * (3.0 - 2.0 * t))
+            }
+            None => pos.lerp(target, (CAMERA_FOLLOW_SPEED * dt).min(1.0)),
+        };
+
+        transform.translation = next.extend(transform.translation.z);
+    }
+
+    if focus.blend.is_some_and(|blend| blend.elapsed >= transition) {
+        focus.blend = None;
+    }
+}
+
+/// Whether debug visuals are drawn: the cursor position, the box select band,
+/// the drop ghost, pickup previews, kept-selection and pile validity outlines,
+/// and the outlines of the foundations and discard zone.
+#[derive(Resource, PartialEq)]
+struct DebugGizmos(bool);
+
+fn toggle_debug_gizmos(keys: Res<Input<KeyCode>>, mut debug_gizmos: ResMut<DebugGizmos>) {
+    if keys.just_pressed(KeyCode::F1) {
+        debug_gizmos.0 = !debug_gizmos.0;
+    }
+}
+
+/// Accessibility mode: gizmo outlines are drawn thicker and at full brightness,
+/// and cards switch to the stark `CardAssets` contrast textures.
+#[derive(Resource)]
+struct HighContrast(bool);
+
+impl HighContrast {
+    /// `color` as an outline should be drawn in the current mode.
+    fn outline(&self, color: Color) -> Color {
+        if !self.0 {
+            return color;
+        }
+
+        // Keep the hue but lift it towards white and drop any transparency.
+        let [r, g, b, _] = color.as_rgba_f32();
+        let lift = |channel: f32| channel + (1.0 - channel) * 0.4;
+        Color::rgb(lift(r), lift(g), lift(b))
+    }
+}
+
+const GIZMO_LINE_WIDTH: f32 = 2.0;
+const HIGH_CONTRAST_LINE_WIDTH: f32 = 5.0;
+
+fn toggle_high_contrast(keys: Res<Input<KeyCode>>, mut contrast: ResMut<HighContrast>) {
+    if keys.just_pressed(KeyCode::X) {
+        contrast.0 = !contrast.0;
+    }
+}
+
+fn apply_high_contrast(contrast: Res<HighContrast>, mut config: ResMut<GizmoConfig>) {
+    if !contrast.is_changed() {
+        return;
+    }
+
+    config.line_width = if contrast.0 {
+        HIGH_CONTRAST_LINE_WIDTH
+    } else {
+        GIZMO_LINE_WIDTH
+    };
+}
+
+/// Whether `draw_grid` outlines the snapping cells.
+#[derive(Resource)]
+struct ShowGrid(bool);
+
+fn toggle_show_grid(keys: Res<Input<KeyCode>>, mut show_grid: ResMut<ShowGrid>) {
+    if keys.just_pressed(KeyCode::B) {
+        show_grid.0 = !show_grid.0;
+    }
+}
+
+/// Cycles how cards line up in their cells, in every grid region at once.
+/// Cards already resting stay put; only later drops use the new anchor, though
+/// the foundations and pile markers move to it straight away.
+fn cycle_grid_anchor(
+    keys: Res<Input<KeyCode>>,
+    mut regions: ResMut<GridRegions>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keys.just_pressed(KeyCode::N) {
+        return;
+    }
+
+    let anchor = regions.fallback.anchor.next();
+    regions.fallback.anchor = anchor;
+    for (_, config) in &mut regions.regions {
+        config.anchor = anchor;
+    }
+    toasts.send(Toast(format!("Snapping cards by {anchor:?}")));
+}
+
+/// Lines cards up in their cells by the size of the card texture, once it has
+/// loaded, rather than the size it is expected to be.
+fn fit_grid_to_cards(
+    card_assets: Res<CardAssets>,
+    images: Res<Assets<Image>>,
+    mut regions: ResMut<GridRegions>,
+) {
+    let Some(image) = images.get(&card_assets.front) else {
+        return;
+    };
+
+    let size = image.size_f32() * CARD_SIZE.truncate();
+    if regions.fallback.card_size != size {
+        regions.set_card_size(size);
+    }
+}
+
+/// Moves the foundations and pile markers to wherever the grid now snaps their
+/// cells to.
+fn refit_piles_to_grid(
+    regions: Res<GridRegions>,
+    slots: Query<Entity, With<PileSlot>>,
+    mut foundations: ResMut<Foundations>,
+    mut commands: Commands,
+) {
+    if !regions.is_changed() {
+        return;
+    }
+
+    *foundations = Foundations::top_right(&regions);
+    for entity in &slots {
+        commands.entity(entity).despawn();
+    }
+    spawn_pile_slots(&regions, &foundations, &mut commands);
+}
+
+/// Faint lines along every cell boundary on the board, region by region, to
+/// check what `align_grid` snaps to.
+fn draw_grid(
+    show_grid: Res<ShowGrid>,
+    regions: Res<GridRegions>,
+    board: Res<BoardBounds>,
+    mut gizmos: Gizmos,
+) {
+    if !show_grid.0 {
+        return;
+    }
+
+    let color = Color::rgba(1.0, 1.0, 1.0, 0.1);
+    let fallback = (board.0, regions.fallback);
+    let areas = if regions.regions.is_empty() {
+        vec![fallback]
+    } else {
+        regions.regions.clone()
+    };
+
+    for (rect, grid) in areas {
+        let area = rect.intersect(board.0);
+        if area.is_empty() {
+            continue;
+        }
+
+        // First boundary at or after the area's lower edge, on each axis.
+        let first =
+            ((area.min - grid.origin) / grid.cell_size).ceil() * grid.cell_size + grid.origin;
+
+        let mut x = first.x;
+        while x <= area.max.x {
+            gizmos.line_2d(vec2(x, area.min.y), vec2(x, area.max.y), color);
+            x += grid.cell_size.x;
+        }
+
+        let mut y = first.y;
+        while y <= area.max.y {
+            gizmos.line_2d(vec2(area.min.x, y), vec2(area.max.x, y), color);
+            y += grid.cell_size.y;
+        }
+    }
+}
+
+/// Whether `highlight_cursor_cell` marks the grid cell under the cursor.
+#[derive(Resource)]
+struct ShowCursorCell(bool);
+
+fn toggle_cursor_cell(keys: Res<Input<KeyCode>>, mut show_cell: ResMut<ShowCursorCell>) {
+    if keys.just_pressed(KeyCode::I) {
+        show_cell.0 = !show_cell.0;
+    }
+}
+
+/// How far inside its cell the cursor cell outline sits, so it reads as the
+/// cell rather than the grid lines around it.
+const CURSOR_CELL_INSET: f32 = 4.0;
+
+/// A faint outline of the cell under the cursor, for planning where cards go.
+/// Hidden while cards are carried, when the drag ghost already shows the target.
+fn highlight_cursor_cell(
+    show_cell: Res<ShowCursorCell>,
+    regions: Res<GridRegions>,
+    world_cursor: Res<WordCursor>,
+    carried: Query<(), SelectedCard>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    if !show_cell.0 || !carried.is_empty() {
+        return;
+    }
+
+    let cell = regions
+        .config_at(world_cursor.0)
+        .cell_rect(world_cursor.0)
+        .inset(-CURSOR_CELL_INSET);
+    gizmos.rect_2d(
+        cell.center(),
+        0.0,
+        cell.size(),
+        contrast.outline(Color::rgba(1.0, 1.0, 1.0, 0.2)),
+    );
+}
+
+const CARD_SIZE: Vec3 = Vec3::new(0.5, 0.5, 1.0);
+
+/// Snaps a point into its cell, in whichever grid region it falls.
+fn align_grid(regions: &GridRegions, point: Vec2) -> Vec2 {
+    regions.config_at(point).snap(point)
+}
+
+/// The card back a spawned card gets.
+#[derive(Resource, Default)]
+struct SpawnTemplate {
+    back: BackTheme,
+}
+
+/// Whether each spawn moves `SpawnTemplate` on to the next back theme.
+#[derive(Resource)]
+struct CycleOnSpawn(bool);
+
+/// A key that spawns at the cursor, alongside the right-click binding.
+#[derive(Resource)]
+struct SpawnKey(Option<KeyCode>);
+
+/// Upper bound on live cards, so spawning can't run away.
+#[derive(Resource, Deref)]
+struct MaxCards(usize);
+
+/// The `MaxCards` limit and the cards counting against it.
+#[derive(SystemParam)]
+struct CardLimit<'w, 's> {
+    max: Res<'w, MaxCards>,
+    cards: Query<'w, 's, (), With<Card>>,
+}
+
+impl CardLimit<'_, '_> {
+    fn on_board(&self) -> usize {
+        self.cards.iter().count()
+    }
+
+    fn reached_toast(&self) -> Toast {
+        Toast(format!("Card limit of {} reached", self.max.0))
+    }
+}
+
+/// What spawning cards from the deck takes: the deck to draw from, the
+/// textures to show them with, and the event announcing each one.
+#[derive(SystemParam)]
+struct CardSpawner<'w> {
+    deck: ResMut<'w, Deck>,
+    assets: Res<'w, CardAssets>,
+    spawned: EventWriter<'w, CardSpawned>,
+}
+
+/// The right click or `SpawnKey` press asking for a card at the cursor.
+#[derive(SystemParam)]
+struct SpawnRequest<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    key: Res<'w, SpawnKey>,
+    clicked: EventReader<'w, 's, RightClicked>,
+}
+
+impl SpawnRequest<'_, '_> {
+    fn requested(&mut self) -> bool {
+        let clicked = self
+            .clicked
+            .read()
+            .any(|RightClicked(action)| *action == RightClickAction::SpawnCard);
+        let pressed = self.key.0.is_some_and(|key| self.keys.just_pressed(key));
+        clicked || pressed
+    }
+}
+
+/// How a card spawned at the cursor looks and arrives.
+#[derive(SystemParam)]
+struct SpawnSettings<'w> {
+    from: Res<'w, SpawnFrom>,
+    cycle: Res<'w, CycleOnSpawn>,
+    template: ResMut<'w, SpawnTemplate>,
+}
+
+/// Where newly spawned cards slide in from on their way to the spawn position.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+enum SpawnFrom {
+    #[default]
+    Cursor,
+    Stock,
+    Origin,
+}
+
+impl SpawnFrom {
+    fn start(self, cursor: Vec2) -> Vec2 {
+        match self {
+            SpawnFrom::Cursor => cursor,
+            SpawnFrom::Stock => STOCK_POS,
+            SpawnFrom::Origin => Vec2::ZERO,
+        }
+    }
+}
+
+/// What a right click does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum RightClickAction {
+    SpawnCard,
+    /// Shows the card under the cursor in the inspector.
+    ContextMenu,
+    /// Sends carried cards back to where they were picked up.
+    CancelDrag,
+    None,
+}
+
+/// The `RightClickAction` for each context a right click can land in. When
+/// several apply, carrying cards beats being over a card, which beats empty
+/// space, and only the winning context's action happens.
+#[derive(Resource)]
+struct RightClickBindings {
+    carrying: RightClickAction,
+    over_card: RightClickAction,
+    empty_space: RightClickAction,
+}
+
+/// The one action a right click resolved to this frame.
+#[derive(Event)]
+struct RightClicked(RightClickAction);
+
+fn resolve_right_click(
+    buttons: Res<Input<MouseButton>>,
+    world_cursor: Res<WordCursor>,
+    bindings: Res<RightClickBindings>,
+    selected: Query<(), SelectedCard>,
+    cards: Query<(&Bounds, &Transform), Interactive>,
+    mut clicked: EventWriter<RightClicked>,
+) {
+    if !buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let over_card = cards
+        .iter()
+        .any(|(bounds, transform)| hit_test(bounds.0, transform.rotation, world_cursor.0));
+
+    let action = if !selected.is_empty() {
+        bindings.carrying
+    } else if over_card {
+        bindings.over_card
+    } else {
+        bindings.empty_space
+    };
+
+    if action != RightClickAction::None {
+        clicked.send(RightClicked(action));
+    }
+}
+
+fn cancel_drag(
+    mut clicked: EventReader<RightClicked>,
+    carried: Query<(Entity, &DragOrigin), SelectedCard>,
+    mut commands: Commands,
+) {
+    if !clicked
+        .read()
+        .any(|RightClicked(action)| *action == RightClickAction::CancelDrag)
+    {
+        return;
+    }
+
+    for (entity, origin) in &carried {
+        commands
+            .entity(entity)
+            .remove::<Selected>()
+            .insert(Dragging(origin.0));
+    }
+}
+
+fn open_context_menu(
+    mut clicked: EventReader<RightClicked>,
+    world_cursor: Res<WordCursor>,
+    cards: Query<(Entity, &Bounds, &Transform), Interactive>,
+    mut inspected: ResMut<Inspected>,
+) {
+    if !clicked
+        .read()
+        .any(|RightClicked(action)| *action == RightClickAction::ContextMenu)
+    {
+        return;
+    }
+
+    inspected.0 = cards
+        .iter()
+        .filter(|(_, bounds, transform)| hit_test(bounds.0, transform.rotation, world_cursor.0))
+        .max_by(|(_, _, a), (_, _, b)| a.translation.z.total_cmp(&b.translation.z))
+        .map(|(entity, ..)| entity);
+}
+
+fn create_card(
+    world_cursor: Res<WordCursor>,
+    mut request: SpawnRequest,
+    mut settings: SpawnSettings,
+    limit: CardLimit,
+    mut spawner: CardSpawner,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    if !request.requested() {
+        return;
+    }
+
+    // Refuse before cycling the template, so no theme is skipped.
+    if limit.on_board() >= limit.max.0 {
+        toasts.send(limit.reached_toast());
+        return;
+    }
+
+    let Some(card) = spawner.deck.draw() else {
+        toasts.send(Toast("The deck is empty".to_owned()));
+        return;
+    };
+
+    let entity = spawn_sliding(
+        world_cursor.0,
+        settings.from.start(world_cursor.0),
+        card,
+        Face::Down,
+        &spawner.assets,
+        &mut commands,
+    );
+    commands
+        .entity(entity)
+        .insert(spawner.assets.back(settings.template.back));
+    spawner.spawned.send(CardSpawned);
+
+    if settings.cycle.0 {
+        settings.template.back = settings.template.back.next();
+    }
+}
+
+/// Deals this many cards from the deck at the cell under the cursor.
+#[derive(Event)]
+struct DealHand(usize);
+
+fn deal_key(keys: Res<Input<KeyCode>>, mut deal: EventWriter<DealHand>) {
+    if keys.just_pressed(KeyCode::H) {
+        deal.send(DealHand(HAND_SIZE));
+    }
+}
+
+/// Deals a fresh hand once the board has been empty for `delay` seconds, for
+/// demos that play themselves.
+#[derive(Resource)]
+struct AutoDeal {
+    enabled: bool,
+    delay: f32,
+}
+
+/// Any spawn, say a right click while the timer runs, cancels the pending deal.
+fn auto_deal(
+    config: Res<AutoDeal>,
+    deck: Res<Deck>,
+    cards: Query<(), With<Card>>,
+    time: Res<Time>,
+    mut spawned: EventReader<CardSpawned>,
+    mut pending: Local<Option<Timer>>,
+    mut deal: EventWriter<DealHand>,
+) {
+    let spawning = spawned.read().count() > 0;
+    if !config.enabled || spawning || !cards.is_empty() || deck.is_empty() {
+        *pending = None;
+        return;
+    }
+
+    let timer = pending.get_or_insert_with(|| Timer::from_seconds(config.delay, TimerMode::Once));
+    if timer.tick(time.delta()).just_finished() {
+        deal.send(DealHand(HAND_SIZE));
+        *pending = None;
+    }
+}
+
+/// How `deal_hand` lays a hand out, and the grid it lands on.
+#[derive(SystemParam)]
+struct HandLayout<'w> {
+    pattern: Res<'w, DealPattern>,
+    source: Res<'w, DealSource>,
+    facing: Res<'w, DealFacing>,
+    regions: Res<'w, GridRegions>,
+}
+
+/// Lays dealt cards out by the `DealPattern`. Cards are drawn from the
+/// `DealSource` end of the deck and fly in from the stock, landing with the
+/// `DealFacing` side up.
+fn deal_hand(
+    mut deals: EventReader<DealHand>,
+    world_cursor: Res<WordCursor>,
+    layout: HandLayout,
+    limit: CardLimit,
+    mut spawner: CardSpawner,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    let HandLayout {
+        pattern,
+        source,
+        facing,
+        regions,
+    } = &layout;
+    let mut on_board = limit.on_board();
+
+    for deal in deals.read() {
+        let room = limit.max.0.saturating_sub(on_board);
+        let count = deal.0.min(spawner.deck.len()).min(room);
+        if count == 0 {
+            let toast = if spawner.deck.is_empty() {
+                Toast("The deck is empty".to_owned())
+            } else {
+                limit.reached_toast()
+            };
+            toasts.send(toast);
+            continue;
+        }
+
+        let grid = regions.config_at(world_cursor.0);
+        let first = grid.snap(world_cursor.0);
+        let face = match **facing {
+            DealFacing::FaceUp => Face::Up,
+            DealFacing::FaceDown => Face::Down,
+        };
+
+        for (slot, cell) in pattern.cells(count).into_iter().enumerate() {
+            let Some(card) = source.draw(&mut spawner.deck) else {
+                break;
+            };
+
+            let pos = first + cell.as_vec2() * grid.cell_size;
+            let entity = spawn_sliding(pos, STOCK_POS, card, face, &spawner.assets, &mut commands);
+            if **pattern == DealPattern::Fan {
+                commands.entity(entity).insert(FanSlot(slot, count));
+            }
+            spawner.spawned.send(CardSpawned);
+        }
+        on_board += count;
+    }
+}
+
+/// Spawns `card` resting at `pos`, showing `face`, with everything a placed
+/// card has. The card lands in the pile at `pos` on the next update rather than
+/// sliding in from somewhere.
+///
+/// Every card in play is spawned through here, so layouts built by hand (tests,
+/// puzzles) get exactly the components the game's own spawns do.
+pub fn spawn_card_at(
+    commands: &mut Commands,
+    card_assets: &CardAssets,
+    pos: Vec2,
+    card: CardData,
+    face: Face,
+) -> Entity {
+    let texture = match face {
+        Face::Up => card_assets.front.clone(),
+        Face::Down => card_assets.back(BackTheme::default()),
+    };
+
+    let entity = spawn_card(pos, pos, texture, commands);
+    commands
+        .entity(entity)
+        .insert((card, face))
+        .with_children(|parent| {
+            let mut label = card_label(card);
+            if face == Face::Down {
+                label.visibility = Visibility::Hidden;
+            }
+            parent.spawn(label);
+        });
+    entity
+}
+
+/// Spawns a card showing `card`'s face, sliding in from `from`.
+fn spawn_face_up(
+    pos: Vec2,
+    from: Vec2,
+    card: CardData,
+    card_assets: &CardAssets,
+    commands: &mut Commands,
+) -> Entity {
+    spawn_sliding(pos, from, card, Face::Up, card_assets, commands)
+}
+
+/// Spawns `card` showing `face`, sliding in from `from` to `pos`.
+fn spawn_sliding(
+    pos: Vec2,
+    from: Vec2,
+    card: CardData,
+    face: Face,
+    card_assets: &CardAssets,
+    commands: &mut Commands,
+) -> Entity {
+    let entity = spawn_card_at(commands, card_assets, pos, card, face);
+    commands
+        .entity(entity)
+        .insert(Transform::from_translation(from.extend(0.0)).with_scale(CARD_SIZE));
+    entity
+}
+
+/// Whether a Klondike-style tableau is dealt from the deck when play starts.
+#[derive(Resource)]
+struct DealTableauOnStart(bool);
+
+const TABLEAU_PILES: usize = 7;
+
+/// Deals seven piles of one to seven cards, fanned with only their top card
+/// face up. Cards go round the piles one row at a time, as a real deal does.
+fn deal_starting_tableau(
+    deal: Res<DealTableauOnStart>,
+    regions: Res<GridRegions>,
+    extent: Res<MaxPileExtent>,
+    source: Res<DealSource>,
+    mut spawner: CardSpawner,
+    mut commands: Commands,
+) {
+    if !deal.0 {
+        return;
+    }
+
+    let mut targets = Vec::new();
+    'deal: for slot in 0..TABLEAU_PILES {
+        for pile in slot..TABLEAU_PILES {
+            let Some(card) = source.draw(&mut spawner.deck) else {
+                break 'deal;
+            };
+
+            targets.push(DealTarget {
+                pile: tableau_pile_pos(&regions, pile),
+                slot: FanSlot(slot, pile + 1),
+                card,
+                face: if slot == pile { Face::Up } else { Face::Down },
+            });
+        }
+    }
+
+    let dealt = deal_sequence(&mut commands, &spawner.assets, &extent, &targets);
+    spawner
+        .spawned
+        .send_batch(dealt.into_iter().map(|_| CardSpawned));
+}
+
+/// Where the `pile`th tableau pile of the starting deal goes.
+fn tableau_pile_pos(regions: &GridRegions, pile: usize) -> Vec2 {
+    align_grid(regions, vec2(-450.0 + pile as f32 * 150.0, 100.0))
+}
+
+/// How a `deal_sequence` plays out.
+#[derive(Resource)]
+struct DealTiming {
+    /// Seconds between one card leaving the stock and the next.
+    stagger: f32,
+    /// Seconds each card spends in the air.
+    flight: f32,
+}
+
+/// Where one card of a `deal_sequence` ends up.
+struct DealTarget {
+    pile: Vec2,
+    slot: FanSlot,
+    card: CardData,
+    face: Face,
+}
+
+/// A card flying from the stock to its place. It can't be touched until it
+/// has landed.
+#[derive(Component)]
+struct DealAnimation {
+    to: Vec2,
+    pile: Vec2,
+    t: f32,
+}
+
+/// A dealt card waiting on the stock for its turn to fly, as untouchable as
+/// one in the air. Cards set off in `order`.
+#[derive(Component)]
+struct QueuedDeal {
+    order: usize,
+    to: Vec2,
+    pile: Vec2,
+}
+
+/// The most `DealAnimation`s in the air at once. The rest stay queued on the
+/// stock until one lands.
+#[derive(Resource)]
+struct MaxConcurrentDeals(usize);
+
+/// Queues every target's card on the stock, for `start_queued_deals` to send
+/// off one at a time.
+fn deal_sequence(
+    commands: &mut Commands,
+    card_assets: &CardAssets,
+    extent: &MaxPileExtent,
+    targets: &[DealTarget],
+) -> Vec<Entity> {
+    targets
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            let entity =
+                spawn_card_at(commands, card_assets, target.pile, target.card, target.face);
+            let FanSlot(slot, count) = target.slot;
+
+            commands.entity(entity).remove::<Dragging>().insert((
+                target.slot,
+                QueuedDeal {
+                    order: i,
+                    to: target.pile + pile_offset_for(slot, count, extent.0),
+                    pile: target.pile,
+                },
+                // Later cards are dealt over the earlier ones still on the stock.
+                Transform::from_translation(STOCK_POS.extend(40.0 - i as f32 * 0.01))
+                    .with_scale(CARD_SIZE),
+            ));
+            entity
+        })
+        .collect()
+}
+
+fn deal_animation(
+    mut query: Query<(Entity, &mut Transform, &mut DealAnimation)>,
+    timing: Res<DealTiming>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut deal) in &mut query {
+        deal.t = (deal.t + time.delta_seconds() / timing.flight.max(f32::EPSILON)).min(1.0);
+        let eased = 1.0 - (1.0 - deal.t).powi(3);
+        let pos = STOCK_POS.lerp(deal.to, eased);
+        transform.translation = pos.extend(transform.translation.z);
+
+        if deal.t >= 1.0 {
+            // Land through the usual path, already in place.
+            commands
+                .entity(entity)
+                .remove::<DealAnimation>()
+                .insert(Dragging(deal.pile));
+        }
+    }
+}
+
+/// Sends queued cards off `stagger` apart, holding back while
+/// `MaxConcurrentDeals` are already in the air.
+fn start_queued_deals(
+    queued: Query<(Entity, &QueuedDeal)>,
+    in_flight: Query<(), With<DealAnimation>>,
+    max: Res<MaxConcurrentDeals>,
+    timing: Res<DealTiming>,
+    time: Res<Time>,
+    mut since_start: Local<f32>,
+    mut commands: Commands,
+) {
+    if queued.is_empty() {
+        // The first card of the next deal sets off straight away.
+        *since_start = timing.stagger;
+        return;
+    }
+    *since_start += time.delta_seconds();
+
+    let mut waiting = queued.iter().collect::<Vec<_>>();
+    waiting.sort_by_key(|(entity, queued)| (queued.order, *entity));
+    let flying = in_flight.iter().count();
+
+    for (flying, (entity, queued)) in (flying..).zip(waiting) {
+        if flying >= max.0.max(1) || *since_start < timing.stagger {
+            break;
+        }
+        *since_start -= timing.stagger;
+
+        commands
+            .entity(entity)
+            .remove::<QueuedDeal>()
+            .insert(DealAnimation {
+                to: queued.to,
+                pile: queued.pile,
+                t: 0.0,
+            });
+    }
+
+    // Time spent waiting for a free slot doesn't bank up into a burst; the
+    // next card goes as soon as one lands.
+    *since_start = since_start.min(timing.stagger);
+}
+
+/// What the console's commands are passed on as, and its replies.
+#[derive(SystemParam)]
+struct ConsoleEvents<'w> {
+    despawn: EventWriter<'w, DespawnCard>,
+    reshuffled: EventWriter<'w, Reshuffled>,
+    deal: EventWriter<'w, DealHand>,
+    query_pile: EventWriter<'w, QueryPile>,
+    compact: EventWriter<'w, CompactPiles>,
+    output: EventWriter<'w, ConsoleOutput>,
+}
+
+/// Cards looked up by `CardId`, and the one being inspected.
+#[derive(SystemParam)]
+struct Inspect<'w> {
+    ids: Res<'w, CardIds>,
+    inspected: ResMut<'w, Inspected>,
+}
+
+/// Carries out commands typed into the dev console.
+fn run_console_commands(
+    mut input: EventReader<ConsoleInput>,
+    world_cursor: Res<WordCursor>,
+    cards: Query<Entity, With<Card>>,
+    mut inspect: Inspect,
+    mut spawner: CardSpawner,
+    mut events: ConsoleEvents,
+    mut commands: Commands,
+) {
+    for ConsoleInput(command) in input.read() {
+        let message = match *command {
+            ConsoleCommand::Spawn(card) => {
+                spawn_face_up(
+                    world_cursor.0,
+                    world_cursor.0,
+                    card,
+                    &spawner.assets,
+                    &mut commands,
+                );
+                spawner.spawned.send(CardSpawned);
+                format!("spawned {card}")
+            }
+            ConsoleCommand::Clear => {
+                let count = cards.iter().count();
+                for entity in &cards {
+                    events.despawn.send(DespawnCard(entity));
+                }
+                format!("cleared {count} cards")
+            }
+            ConsoleCommand::Shuffle(seed) => {
+                let deck = &mut spawner.deck;
+                match seed {
+                    Some(seed) => deck.shuffle(&mut StdRng::seed_from_u64(seed)),
+                    None => deck.shuffle(&mut rand::thread_rng()),
+                }
+                events.reshuffled.send(Reshuffled);
+                format!("shuffled {} cards", deck.len())
+            }
+            ConsoleCommand::Sort => {
+                spawner.deck.sort_standard();
+                format!("sorted {} cards", spawner.deck.len())
+            }
+            ConsoleCommand::Deal(count) => {
+                events.deal.send(DealHand(count));
+                format!("dealing {count} cards")
+            }
+            // Answered by `configure_deal`, `configure_placement`,
+            // `run_tag_commands`, `save_board` and `load_board`.
+            ConsoleCommand::DealFrom(_)
+            | ConsoleCommand::DealFacing(_)
+            | ConsoleCommand::RecallOrder(_)
+            | ConsoleCommand::CompleteAnimation(_)
+            | ConsoleCommand::SpawnFrom(_)
+            | ConsoleCommand::PlacementRule(_)
+            | ConsoleCommand::Tag(..)
+            | ConsoleCommand::Untag(..)
+            | ConsoleCommand::Tagged(_)
+            | ConsoleCommand::Save
+            | ConsoleCommand::Load => continue,
+            ConsoleCommand::Inspect(id) => match inspect.ids.get(id) {
+                Some(entity) => {
+                    inspect.inspected.0 = Some(entity);
+                    format!("inspecting card {}", id.0)
+                }
+                None => format!("no card with id {}", id.0),
+            },
+            ConsoleCommand::Pile => {
+                events.query_pile.send(QueryPile(world_cursor.0));
+                continue;
+            }
+            ConsoleCommand::Compact => {
+                events.compact.send(CompactPiles);
+                "compacting piles".to_owned()
+            }
+        };
+        events.output.send(ConsoleOutput(message));
+    }
+}
+
+/// Deal, recall and completion settings changed from the console.
+fn configure_deal(
+    mut input: EventReader<ConsoleInput>,
+    mut source: ResMut<DealSource>,
+    mut facing: ResMut<DealFacing>,
+    mut recall_order: ResMut<RecallOrder>,
+    mut complete_animation: ResMut<CompleteAnimation>,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for ConsoleInput(command) in input.read() {
+        let message = match *command {
+            ConsoleCommand::DealFrom(from) => {
+                *source = from;
+                format!("dealing from the {from:?}")
+            }
+            ConsoleCommand::DealFacing(face) => {
+                *facing = face;
+                format!("dealing {face:?}")
+            }
+            ConsoleCommand::RecallOrder(order) => {
+                *recall_order = order;
+                format!("recalling cards {order:?}")
+            }
+            ConsoleCommand::CompleteAnimation(animation) => {
+                *complete_animation = animation;
+                format!("completed sets leave by {animation:?}")
+            }
+            _ => continue,
+        };
+        output.send(ConsoleOutput(message));
+    }
+}
+
+/// Spawn and placement settings changed from the console.
+fn configure_placement(
+    mut input: EventReader<ConsoleInput>,
+    mut spawn_from: ResMut<SpawnFrom>,
+    mut rule: ResMut<PlacementRule>,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for ConsoleInput(command) in input.read() {
+        let message = match *command {
+            ConsoleCommand::SpawnFrom(from) => {
+                *spawn_from = from;
+                format!("spawning from the {from:?}")
+            }
+            ConsoleCommand::PlacementRule(placement) => {
+                *rule = placement;
+                format!("placing by {placement:?}")
+            }
+            _ => continue,
+        };
+        output.send(ConsoleOutput(message));
+    }
+}
+
+/// `tag`, `untag` and `tagged`, for trying out rules that branch on tags.
+fn run_tag_commands(
+    mut input: EventReader<ConsoleInput>,
+    ids: Res<CardIds>,
+    mut cards: Query<(Entity, &CardId, &mut Tags), With<Card>>,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for ConsoleInput(command) in input.read() {
+        let message = match command {
+            ConsoleCommand::Tag(id, tag) | ConsoleCommand::Untag(id, tag) => {
+                let tagging = matches!(command, ConsoleCommand::Tag(..));
+                match ids.get(*id).and_then(|entity| cards.get_mut(entity).ok()) {
+                    None => format!("no card with id {}", id.0),
+                    Some((_, _, mut tags)) if tagging => match tags.add(tag.clone()) {
+                        Ok(true) => format!("tagged card {} {tag}", id.0),
+                        Ok(false) => format!("card {} is already {tag}", id.0),
+                        Err(err) => err,
+                    },
+                    Some((_, _, mut tags)) => {
+                        if tags.remove(tag) {
+                            format!("untagged card {} {tag}", id.0)
+                        } else {
+                            format!("card {} isn't {tag}", id.0)
+                        }
+                    }
+                }
+            }
+            ConsoleCommand::Tagged(tag) => {
+                let mut tagged = cards
+                    .iter()
+                    .filter(|(_, _, tags)| tags.has(tag))
+                    .map(|(_, id, _)| id.0)
+                    .collect::<Vec<_>>();
+                tagged.sort_unstable();
+                if tagged.is_empty() {
+                    format!("no cards are {tag}")
+                } else {
+                    let ids = tagged.iter().map(u64::to_string).collect::<Vec<_>>();
+                    format!("{tag}: {}", ids.join(" "))
+                }
+            }
+            _ => continue,
+        };
+        output.send(ConsoleOutput(message));
+    }
+}
+
+type SavedCardQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static CardId,
+        &'static CardData,
+        &'static Face,
+        &'static Tags,
+        &'static Transform,
+        Option<&'static Dragging>,
+        Option<&'static Pile>,
+    ),
+>;
+
+/// `save` writes every card to `SaveConfig::path`, bottom of each pile first.
+fn save_board(
+    mut input: EventReader<ConsoleInput>,
+    config: Res<SaveConfig>,
+    regions: Res<GridRegions>,
+    index: Res<PileIndex>,
+    cards: SavedCardQuery,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for _ in input
+        .read()
+        .filter(|ConsoleInput(command)| *command == ConsoleCommand::Save)
+    {
+        let mut saved = cards
+            .iter()
+            .map(
+                |(entity, id, card, face, tags, transform, dragging, pile)| {
+                    let height = pile
+                        .and_then(|pile| index.get(pile))
+                        .and_then(|cards| cards.iter().position(|card| *card == entity))
+                        .unwrap_or_default();
+                    let mut tags = tags.iter().cloned().collect::<Vec<_>>();
+                    tags.sort();
+                    let card = SavedCard {
+                        id: *id,
+                        card: *card,
+                        // Where the card rests, not where it is mid-slide or
+                        // fanned out; loading fans it again.
+                        position: match (dragging, pile) {
+                            (Some(dragging), _) => dragging.0,
+                            (None, Some(pile)) => {
+                                align_grid(&regions, Vec2::new(pile.0 as f32, pile.1 as f32))
+                            }
+                            (None, None) => transform.translation.truncate(),
+                        },
+                        face: *face,
+                        tags,
+                    };
+                    (height, card)
+                },
+            )
+            .collect::<Vec<_>>();
+        saved.sort_by_key(|(height, card)| (*height, card.id));
+
+        let save = BoardSave::new(saved.into_iter().map(|(_, card)| card).collect());
+        let message = match std::fs::write(&config.path, save.to_text()) {
+            Ok(()) => format!(
+                "saved {} cards to {}",
+                save.cards.len(),
+                config.path.display()
+            ),
+            Err(err) => format!("couldn't save to {}: {err}", config.path.display()),
+        };
+        output.send(ConsoleOutput(message));
+    }
+}
+
+/// `load` replaces every card on the board with those in `SaveConfig::path`.
+/// Cards keep their saved `CardId`s, and `NextCardId` moves past them as they
+/// are indexed, so no id is handed out twice.
+fn load_board(
+    mut input: EventReader<ConsoleInput>,
+    config: Res<SaveConfig>,
+    card_assets: Res<CardAssets>,
+    cards: Query<Entity, With<Card>>,
+    mut despawn: EventWriter<DespawnCard>,
+    mut output: EventWriter<ConsoleOutput>,
+    mut commands: Commands,
+) {
+    for _ in input
+        .read()
+        .filter(|ConsoleInput(command)| *command == ConsoleCommand::Load)
+    {
+        let save = std::fs::read_to_string(&config.path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| BoardSave::from_text(&text).map_err(|err| err.to_string()));
+        let save = match save {
+            Ok(save) => save,
+            Err(err) => {
+                output.send(ConsoleOutput(format!(
+                    "couldn't load {}: {err}",
+                    config.path.display()
+                )));
+                continue;
+            }
+        };
+
+        for entity in &cards {
+            despawn.send(DespawnCard(entity));
+        }
+
+        // Cards sharing a cell restack in the order they were saved.
+        let mut pile_sizes = HashMap::<Pile, usize>::new();
+        for saved in &save.cards {
+            *pile_sizes.entry(Pile::new(saved.position)).or_default() += 1;
+        }
+        let mut stacked = HashMap::<Pile, usize>::new();
+        for saved in &save.cards {
+            let pile = Pile::new(saved.position);
+            let slot = stacked.entry(pile).or_default();
+            let entity = spawn_card_at(
+                &mut commands,
+                &card_assets,
+                saved.position,
+                saved.card,
+                saved.face,
+            );
+            commands.entity(entity).insert((
+                saved.id,
+                Tags(saved.tags.iter().cloned().collect()),
+                FanSlot(*slot, pile_sizes[&pile]),
+            ));
+            *slot += 1;
+        }
+
+        output.send(ConsoleOutput(format!(
+            "loaded {} cards from {}",
+            save.cards.len(),
+            config.path.display()
+        )));
+    }
+}
+
+/// G switches between deal patterns.
+fn cycle_deal_pattern(
+    keys: Res<Input<KeyCode>>,
+    mut pattern: ResMut<DealPattern>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keys.just_pressed(KeyCode::G) {
+        *pattern = pattern.next();
+        toasts.send(Toast(format!("Dealing in a {}", *pattern)));
+    }
+}
+
+#[derive(Event)]
+struct CardSpawned;
+
+#[derive(Event)]
+struct CardPickedUp(Entity);
+
+/// Sent when a card settles into a pile after being dragged.
+#[derive(Event)]
+struct CardDropped(Entity);
+
+type SelectedCard = (With<Card>, With<Selected>);
+type UnselectedCard = (With<Card>, Without<Selected>);
+
+/// What clicking a card in a pile picks up.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+enum PickupMode {
+    /// Only the top card of the clicked pile.
+    TopOnly,
+    /// The clicked card and every card stacked above it.
+    #[default]
+    Substack,
+}
+
+fn toggle_pickup_mode(keys: Res<Input<KeyCode>>, mut pickup_mode: ResMut<PickupMode>) {
+    if keys.just_pressed(KeyCode::F4) {
+        *pickup_mode = match *pickup_mode {
+            PickupMode::TopOnly => PickupMode::Substack,
+            PickupMode::Substack => PickupMode::TopOnly,
+        };
+    }
+}
+
+type PickupQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Bounds,
+        &'static Transform,
+        Option<&'static Pile>,
+    ),
+    Interactive,
+>;
+
+/// Cards the pointer can reach: not on their way anywhere under an animation,
+/// and given their render layer, so whether they are `Inert` is settled.
+type Interactive = (
+    With<Card>,
+    With<RenderLayers>,
+    Without<Displayed>,
+    Without<Disappearing>,
+    Without<DealAnimation>,
+    Without<QueuedDeal>,
+    Without<Inert>,
+);
+
+/// What picking cards up reads and writes, shared by clicks, touches and the
+/// box select.
+#[derive(SystemParam)]
+struct Pickup<'w, 's> {
+    cards: PickupQuery<'w, 's>,
+    carried: Query<'w, 's, Entity, SelectedCard>,
+    index: Res<'w, PileIndex>,
+    mode: Res<'w, PickupMode>,
+    max: Res<'w, MaxSelection>,
+    next_order: ResMut<'w, NextDragOrder>,
+    picked_up: EventWriter<'w, CardPickedUp>,
+    toasts: EventWriter<'w, Toast>,
+}
+
+impl Pickup<'_, '_> {
+    /// Cards a click at `point` would pick up under the `PickupMode`.
+    fn targets(&self, point: Vec2) -> Vec<Entity> {
+        pickup_targets(*self.mode, point, &self.cards, &self.index)
+    }
+}
+
+/// Cards a click at `point` would pick up, bottom to top.
+fn pickup_targets(
+    mode: PickupMode,
+    point: Vec2,
+    cards: &PickupQuery,
+    index: &PileIndex,
+) -> Vec<Entity> {
+    let Some((clicked, _, _, pile)) = cards
+        .iter()
+        .filter(|(_, bounds, transform, _)| hit_test(bounds.0, transform.rotation, point))
+        .max_by(|(_, _, a, _), (_, _, b, _)| a.translation.z.total_cmp(&b.translation.z))
+    else {
+        return Vec::new();
+    };
+
+    let pile_cards = pile.and_then(|pile| index.get(pile));
+    let targets = match (mode, pile_cards) {
+        (PickupMode::TopOnly, Some(pile_cards)) => pile_cards.last().copied().into_iter().collect(),
+        (PickupMode::Substack, Some(pile_cards)) => pile_cards
+            .iter()
+            .skip_while(|card| **card != clicked)
+            .copied()
+            .collect(),
+        (_, None) => Vec::new(),
+    };
+
+    // A card not yet in the index (or not in a pile) is picked up on its own.
+    if targets.is_empty() {
+        vec![clicked]
+    } else {
+        targets
+    }
+}
+
+/// How a click or box selection combines with the cards already carried.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SelectionModifier {
+    /// Carry just the new cards.
+    Replace,
+    /// Shift: carry the new cards as well.
+    Add,
+    /// Ctrl: pick up uncarried cards and put down carried ones.
+    Toggle,
+}
+
+impl SelectionModifier {
+    fn from_keys(keys: &Input<KeyCode>) -> Self {
+        if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            SelectionModifier::Toggle
+        } else if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            SelectionModifier::Add
+        } else {
+            SelectionModifier::Replace
+        }
+    }
+}
+
+/// Applies a click or box selection of `hits` to the `carried` cards.
+///
+/// At most `MaxSelection` cards end up carried. When `hits` would go over, only
+/// the front-most of the new cards are picked up and a toast says so.
+fn change_selection(
+    modifier: SelectionModifier,
+    hits: &[Entity],
+    carried: &[Entity],
+    pickup: &mut Pickup,
+    commands: &mut Commands,
+) {
+    let kept = carried
+        .iter()
+        .filter(|entity| match modifier {
+            SelectionModifier::Replace => hits.contains(entity),
+            SelectionModifier::Add => true,
+            SelectionModifier::Toggle => !hits.contains(entity),
+        })
+        .count();
+    let added = hits
+        .iter()
+        .filter(|entity| !carried.contains(entity))
+        .copied()
+        .collect::<Vec<_>>();
+    // `hits` runs bottom-most first, so the front-most cards are at the end.
+    let room = pickup.max.0.saturating_sub(kept);
+    let capped = added.len() > room;
+    let added = &added[added.len().saturating_sub(room)..];
+
+    if modifier == SelectionModifier::Replace {
+        for entity in carried.iter().filter(|entity| !hits.contains(entity)) {
+            commands.entity(*entity).remove::<Selected>();
+        }
+    }
+
+    for &entity in hits {
+        match (modifier, carried.contains(&entity)) {
+            (SelectionModifier::Toggle, true) => {
+                commands.entity(entity).remove::<Selected>();
+            }
+            (_, true) => {}
+            (_, false) => {
+                if !added.contains(&entity) {
+                    continue;
+                }
+                let Ok((_, bounds, ..)) = pickup.cards.get(entity) else {
+                    continue;
+                };
+
+                commands.entity(entity).insert((
+                    Selected,
+                    DragOrigin(bounds.center()),
+                    DragVelocity(Vec2::ZERO),
+                    FollowVelocity::default(),
+                    // New cards join the end of the carried fan.
+                    DragOrder(pickup.next_order.0),
+                ));
+                commands.entity(entity).remove::<(
+                    Pile,
+                    LooseCell,
+                    HoverLift,
+                    LandBounce,
+                    SettleProgress,
+                    Compacting,
+                )>();
+                pickup.picked_up.send(CardPickedUp(entity));
+                pickup.next_order.0 = pickup.next_order.0.wrapping_add(1);
+            }
+        }
+    }
+
+    if capped {
+        let toast = selection_capped_toast(&pickup.max);
+        pickup.toasts.send(toast);
+    }
+}
+
+/// The most cards that can be carried at once.
+#[derive(Resource)]
+struct MaxSelection(usize);
+
+fn selection_capped_toast(max: &MaxSelection) -> Toast {
+    Toast(format!("Only the top {} cards can be carried", max.0))
+}
+
+fn select_card(
+    mut pickup: Pickup,
+    world_cursor: Res<WordCursor>,
+    input: DragInput,
+    persist: Res<PersistSelection>,
+    kept: Query<Entity, With<KeptSelection>>,
+    mut box_select: ResMut<BoxSelect>,
+    mut commands: Commands,
+) {
+    let DragInput { buttons, keys, .. } = &input;
+    let carried = pickup.carried.iter().collect::<Vec<_>>();
+    let modifier = SelectionModifier::from_keys(keys);
+    // Without a modifier only one stack is carried at a time. Inspecting a card
+    // shouldn't pick it up.
+    let selecting = carried.is_empty() || modifier != SelectionModifier::Replace;
+
+    let clear_kept = |commands: &mut Commands| {
+        for entity in &kept {
+            commands.entity(entity).remove::<KeptSelection>();
+        }
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        clear_kept(&mut commands);
+    }
+
+    if input.drop_pressed(!carried.is_empty()) {
+        for entity in &carried {
+            commands.entity(*entity).remove::<Selected>();
+            if persist.0 {
+                commands.entity(*entity).insert(KeptSelection);
+            }
+        }
+    } else if buttons.just_pressed(MouseButton::Left)
+        && !inspect_modifier_pressed(keys)
+        && selecting
+    {
+        let targets = pickup.targets(world_cursor.0);
+
+        // Picking up afresh lets go of what was kept. Clicks on empty space are
+        // left to `deselect_on_empty_tap`.
+        if modifier == SelectionModifier::Replace && !targets.is_empty() {
+            clear_kept(&mut commands);
+        }
+
+        if targets.is_empty() {
+            // Clicks on the stock deal from it instead.
+            if !stock_bounds().contains(world_cursor.0) {
+                box_select.0 = Some(world_cursor.0);
+            }
+        } else {
+            change_selection(modifier, &targets, &carried, &mut pickup, &mut commands);
+        }
+    }
+}
+
+/// Whether a plain click on empty space lets go of a kept selection.
+#[derive(Resource)]
+struct TapToDeselect(bool);
+
+/// A plain left-press that would pick nothing up clears the kept selection.
+/// The press still starts a box select as usual. While cards are carried the
+/// press is a drop (or, with a modifier, a selection change), so it's left
+/// alone.
+fn deselect_on_empty_tap(
+    pickup: Pickup,
+    kept: Query<Entity, With<KeptSelection>>,
+    world_cursor: Res<WordCursor>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    tap: Res<TapToDeselect>,
+    mut commands: Commands,
+) {
+    if !tap.0
+        || kept.is_empty()
+        || !pickup.carried.is_empty()
+        || !buttons.just_pressed(MouseButton::Left)
+        || SelectionModifier::from_keys(&keys) != SelectionModifier::Replace
+        || inspect_modifier_pressed(&keys)
+        || stock_bounds().contains(world_cursor.0)
+    {
+        return;
+    }
+
+    if pickup.targets(world_cursor.0).is_empty() {
+        for entity in &kept {
+            commands.entity(entity).remove::<KeptSelection>();
+        }
+    }
+}
+
+/// Where a rubber-band selection started, while one is being dragged out.
+#[derive(Resource, Default)]
+struct BoxSelect(Option<Vec2>);
+
+/// Dragging from empty space draws a band; releasing picks up every card it
+/// touches, combined with what is carried by the held `SelectionModifier`.
+fn box_select(
+    mut pickup: Pickup,
+    world_cursor: Res<WordCursor>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut box_select: ResMut<BoxSelect>,
+    mut commands: Commands,
+) {
+    let Some(start) = box_select.0 else {
+        return;
+    };
+
+    let band = Rect::from_corners(start, world_cursor.0);
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    box_select.0 = None;
+
+    // Bottom-most first, so the fan keeps the cards' stacking order.
+    let mut hits = pickup
+        .cards
+        .iter()
+        .filter(|(_, bounds, ..)| !bounds.0.intersect(band).is_empty())
+        .map(|(entity, _, transform, _)| (entity, transform.translation.z))
+        .collect::<Vec<_>>();
+    hits.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    let hits = hits
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    let carried = pickup.carried.iter().collect::<Vec<_>>();
+    change_selection(
+        SelectionModifier::from_keys(&keys),
+        &hits,
+        &carried,
+        &mut pickup,
+        &mut commands,
+    );
+}
+
+fn show_box_select(
+    box_select: Res<BoxSelect>,
+    world_cursor: Res<WordCursor>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    let Some(start) = box_select.0 else {
+        return;
+    };
+
+    let band = Rect::from_corners(start, world_cursor.0);
+    gizmos.rect_2d(
+        band.center(),
+        0.0,
+        band.size(),
+        contrast.outline(Color::CYAN),
+    );
+}
+
+/// Click once to pick cards up and again, anywhere, to put them down, rather
+/// than holding the button for the whole drag.
+#[derive(Resource)]
+struct StickyDrag(bool);
+
+fn toggle_sticky_drag(keys: Res<Input<KeyCode>>, mut sticky: ResMut<StickyDrag>) {
+    if keys.just_pressed(KeyCode::F9) {
+        sticky.0 = !sticky.0;
+    }
+}
+
+/// Cards stay selected after they are put down, until Escape (once the shuffle
+/// animation and the tutorial have had their turn at it), a new pickup or
+/// (with `TapToDeselect`) a click on empty space, so a dropped group can be
+/// nudged straight away.
+#[derive(Resource)]
+struct PersistSelection(bool);
+
+/// Cards put down while `PersistSelection` is on.
+#[derive(Component)]
+struct KeptSelection;
+
+fn toggle_persist_selection(
+    keys: Res<Input<KeyCode>>,
+    mut persist: ResMut<PersistSelection>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keys.just_pressed(KeyCode::O) {
+        persist.0 = !persist.0;
+        toasts.send(Toast(if persist.0 {
+            "Selection persists after drops".to_owned()
+        } else {
+            "Selection ends on drop".to_owned()
+        }));
+    }
+}
+
+fn outline_kept_selection(
+    kept: Query<&Bounds, With<KeptSelection>>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    for bounds in &kept {
+        gizmos.rect_2d(
+            bounds.center(),
+            0.0,
+            bounds.size() + Vec2::splat(4.0),
+            contrast.outline(Color::CYAN),
+        );
+    }
+}
+
+/// The buttons and keys a carry is put down with, under the `StickyDrag` mode.
+#[derive(SystemParam)]
+struct DragInput<'w> {
+    buttons: Res<'w, Input<MouseButton>>,
+    keys: Res<'w, Input<KeyCode>>,
+    sticky: Res<'w, StickyDrag>,
+}
+
+impl DragInput<'_> {
+    fn drop_pressed(&self, carrying: bool) -> bool {
+        drop_pressed(&self.buttons, &self.keys, &self.sticky, carrying)
+    }
+}
+
+/// Whether the left button puts down what is being carried this frame. Clicks
+/// with a selection modifier change the selection instead.
+fn drop_pressed(
+    buttons: &Input<MouseButton>,
+    keys: &Input<KeyCode>,
+    sticky: &StickyDrag,
+    carrying: bool,
+) -> bool {
+    if SelectionModifier::from_keys(keys) != SelectionModifier::Replace {
+        false
+    } else if sticky.0 {
+        carrying && buttons.just_pressed(MouseButton::Left)
+    } else {
+        buttons.just_released(MouseButton::Left)
+    }
+}
+
+/// Outlines what a click would pick up under the `PickupMode`.
+fn preview_pickup(
+    query: PickupQuery,
+    selected: Query<SelectedCard>,
+    index: Res<PileIndex>,
+    pickup_mode: Res<PickupMode>,
+    world_cursor: Res<WordCursor>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    if !selected.is_empty() {
+        return;
+    }
+
+    for entity in pickup_targets(*pickup_mode, world_cursor.0, &query, &index) {
+        if let Ok((_, bounds, ..)) = query.get(entity) {
+            gizmos.rect_2d(
+                bounds.center(),
+                0.0,
+                bounds.size() + Vec2::splat(4.0),
+                contrast.outline(Color::YELLOW),
+            );
+        }
+    }
+}
+
+/// The top-most card under the cursor, while nothing is being dragged.
+#[derive(Component)]
+struct Hovered;
+
+type NudgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, Option<&'static HoverLift>),
+    (Or<(With<Hovered>, With<KeptSelection>)>, Without<Dragging>),
+>;
+
+/// Shift + arrows move the hovered card, and any kept selection, one grid
+/// cell, repeating while held.
+fn nudge_hovered(
+    keys: Res<Input<KeyCode>>,
+    mut repeating: RepeatingKeys,
+    regions: Res<GridRegions>,
+    hovered: NudgeQuery,
+    mut commands: Commands,
+) {
+    let mut direction = Vec2::ZERO;
+    for (key, step) in [
+        (KeyCode::Left, Vec2::NEG_X),
+        (KeyCode::Right, Vec2::X),
+        (KeyCode::Up, Vec2::Y),
+        (KeyCode::Down, Vec2::NEG_Y),
+    ] {
+        // Asked every frame, so hold times stay right even without Shift.
+        if repeating.repeating_just_pressed(key) {
+            direction += step;
+        }
+    }
+
+    if direction == Vec2::ZERO || !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+
+    for (entity, transform, lift) in &hovered {
+        // From where the card rests, not where the hover has lifted it to.
+        let pos = lift.map_or(transform.translation.truncate(), |lift| lift.base);
+        let target = pos + direction * regions.config_at(pos).cell_size;
+        commands
+            .entity(entity)
+            .remove::<(Pile, LooseCell, FanSlot, HoverLift)>()
+            .insert(Dragging(target));
+    }
+}
+
+/// Resting position of a top card lifted by hovering, restored on un-hover.
+#[derive(Component)]
+struct HoverLift {
+    base: Vec2,
+}
+
+const HOVER_LIFT: Vec2 = Vec2::new(0.0, 12.0);
+
+type HoverQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Bounds,
+        &'static Transform,
+        Option<&'static HoverLift>,
+        Option<&'static Hovered>,
+        With<Card>,
+    ),
+    Interactive,
+>;
+
+fn hover_cards(
+    query: HoverQuery,
+    selected: Query<SelectedCard>,
+    world_cursor: Res<WordCursor>,
+    mut commands: Commands,
+) {
+    // Hit test lifted cards at their resting spot so the lift can't flicker under the cursor.
+    let top = query
+        .iter()
+        .filter(|_| selected.is_empty())
+        .filter(|(_, bounds, transform, lift, _, _)| {
+            let rest = lift.map_or(bounds.0, |lift| {
+                Rect::from_center_size(lift.base, bounds.size())
+            });
+            hit_test(rest, transform.rotation, world_cursor.0)
+        })
+        .max_by(|(_, _, a, ..), (_, _, b, ..)| a.translation.z.total_cmp(&b.translation.z))
+        .map(|(entity, ..)| entity);
+
+    for (entity, _, _, _, hovered, _) in &query {
+        let hover = top == Some(entity);
+        if hover && hovered.is_none() {
+            commands.entity(entity).insert(Hovered);
+        } else if !hover && hovered.is_some() {
+            commands.entity(entity).remove::<Hovered>();
+        }
+    }
+}
+
+type LiftQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static Pile,
+        Option<&'static HoverLift>,
+        Option<&'static Hovered>,
+    ),
+    (With<Card>, Without<Selected>, Without<Dragging>),
+>;
+
+fn lift_hovered(mut query: LiftQuery, index: Res<PileIndex>, mut commands: Commands) {
+    for (entity, mut transform, pile, lift, hovered) in &mut query {
+        let is_top = index.get(pile).and_then(|cards| cards.last()) == Some(&entity);
+        let pos = transform.translation.truncate();
+
+        let next = match (lift, hovered.is_some() && is_top) {
+            (None, true) => {
+                commands.entity(entity).insert(HoverLift { base: pos });
+                continue;
+            }
+            (Some(lift), true) => pos.lerp(lift.base + HOVER_LIFT, 0.3),
+            (Some(lift), false) => {
+                let next = pos.lerp(lift.base, 0.3);
+                if next.distance(lift.base) > 0.5 {
+                    next
+                } else {
+                    commands.entity(entity).remove::<HoverLift>();
+                    lift.base
+                }
+            }
+            (None, false) => continue,
+        };
+
+        transform.translation = next.extend(transform.translation.z);
+    }
+}
+
+/// Whether hovering riffles the whole pile with `fan_hover` instead of just
+/// lifting its top card.
+#[derive(Resource)]
+struct FanHover(bool);
+
+fn fan_hover_enabled(fan_hover: Res<FanHover>) -> bool {
+    fan_hover.0
+}
+
+fn toggle_fan_hover(keys: Res<Input<KeyCode>>, mut fan_hover: ResMut<FanHover>) {
+    if keys.just_pressed(KeyCode::K) {
+        fan_hover.0 = !fan_hover.0;
+    }
+}
+
+/// How many cards from the top of a pile `fan_hover` spreads; 0 means all.
+#[derive(Resource)]
+struct PeekCount(usize);
+
+/// How many cards either side of the focus card are lifted at all.
+const FAN_HOVER_SPREAD: f32 = 3.0;
+
+type FanHoverQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static Bounds,
+        Option<&'static HoverLift>,
+    ),
+    (With<Card>, With<Pile>, Without<Selected>, Without<Dragging>),
+>;
+
+/// Lifts the hovered pile like a riffle: the card at the cursor's position along
+/// the stack lifts by `HOVER_LIFT`, its neighbours by less the further away they
+/// are. Only the top `PeekCount` cards take part; the rest stay put at the
+/// base. Cards drop back to their `HoverLift` base once the pile is left.
+fn fan_hover(
+    mut query: FanHoverQuery,
+    hovered: Query<&Pile, With<Hovered>>,
+    index: Res<PileIndex>,
+    peek_count: Res<PeekCount>,
+    world_cursor: Res<WordCursor>,
+    mut commands: Commands,
+) {
+    let mut lifts = HashMap::<Entity, Vec2>::default();
+
+    if let Some(pile_cards) = hovered.iter().next().and_then(|pile| index.get(pile)) {
+        let bases = pile_cards
+            .iter()
+            .filter_map(|card| {
+                let (_, transform, bounds, lift) = query.get(*card).ok()?;
+                let base = lift.map_or(transform.translation.truncate(), |lift| lift.base);
+                Some((*card, base, bounds.width()))
+            })
+            .collect::<Vec<_>>();
+        let spread = match peek_count.0 {
+            0 => bases.len(),
+            n => n.min(bases.len()),
+        };
+        let bases = &bases[bases.len() - spread..];
+
+        // Where the cursor is across the stack, from the bottom card's left edge
+        // to the top card's right edge, picks the focus card.
+        let (min_x, max_x, width) = bases.iter().fold(
+            (f32::MAX, f32::MIN, 0.0_f32),
+            |(min_x, max_x, width), (_, base, card_width)| {
+                (min_x.min(base.x), max_x.max(base.x), width.max(*card_width))
+            },
+        );
+        let span = (max_x - min_x + width).max(1.0);
+        let along = ((world_cursor.x - (min_x - width / 2.0)) / span).clamp(0.0, 1.0);
+        let focus = along * bases.len().saturating_sub(1) as f32;
+
+        for (i, (card, ..)) in bases.iter().enumerate() {
+            let falloff = (1.0 - (i as f32 - focus).abs() / FAN_HOVER_SPREAD).max(0.0);
+            if falloff > 0.0 {
+                lifts.insert(*card, HOVER_LIFT * falloff);
+            }
+        }
+    }
+
+    for (entity, mut transform, _, lift) in &mut query {
+        let pos = transform.translation.truncate();
+
+        let next = match (lift, lifts.get(&entity)) {
+            (None, Some(_)) => {
+                commands.entity(entity).insert(HoverLift { base: pos });
+                continue;
+            }
+            (Some(lift), Some(offset)) => pos.lerp(lift.base + *offset, 0.3),
+            (Some(lift), None) => {
+                let next = pos.lerp(lift.base, 0.3);
+                if next.distance(lift.base) > 0.5 {
+                    next
+                } else {
+                    commands.entity(entity).remove::<HoverLift>();
+                    lift.base
+                }
+            }
+            (None, None) => continue,
+        };
+
+        transform.translation = next.extend(transform.translation.z);
+    }
+}
+
+/// Where a card was picked up from.
+#[derive(Component, Deref)]
+struct DragOrigin(Vec2);
+
+/// Smoothed on-screen velocity of a card while it is being dragged.
+#[derive(Component, Deref)]
+struct DragVelocity(Vec2);
+
+/// Where carried cards are held: on the grid or free of it, fanned out no
+/// further than the pile extent.
+#[derive(SystemParam)]
+struct CarryLayout<'w> {
+    snap_mode: Res<'w, SnapMode>,
+    regions: Res<'w, GridRegions>,
+    extent: Res<'w, MaxPileExtent>,
+}
+
+type CarriedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        Option<&'static DragVelocity>,
+        Option<&'static mut FollowVelocity>,
+        Option<&'static DragOrder>,
+        Option<&'static Weight>,
+        SelectedCard,
+    ),
+>;
+
+fn drag_selected(
+    mut query: CarriedQuery,
+    world_cursor: Res<WordCursor>,
+    spring: Res<FollowSpring>,
+    layout: CarryLayout,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let CarryLayout {
+        snap_mode,
+        regions,
+        extent,
+    } = &layout;
+    let grid_pos = align_grid(regions, world_cursor.0);
+    let anchor = match **snap_mode {
+        SnapMode::Live => grid_pos,
+        SnapMode::OnRelease => world_cursor.0,
+    };
+
+    // Query order isn't stable between frames, so fan by pickup order instead
+    // and cards never swap places mid-drag. A substack fans bottom to top.
+    let mut selected = query.iter_mut().collect::<Vec<_>>();
+    selected.sort_by_key(|(entity, _, _, _, order, ..)| (order.copied(), *entity));
+    let count = selected.len();
+    let dt = time.delta_seconds().min(MAX_SPRING_STEP);
+
+    for (i, (entity, mut transform, velocity, follow, _, weight, _)) in
+        selected.into_iter().enumerate()
+    {
+        let index = (i as f32) + 1.0;
+        let offset = pile_offset_for(i, count, extent.0);
+
+        let dragging = Dragging(world_cursor.0);
+        let previous = transform.translation;
+
+        // Every card gets the same spring, slackened a little for each slot
+        // further along so the fan trails behind its first card.
+        let mass = weight.copied().unwrap_or_default().get();
+        let stiffness = spring.stiffness / (1.0 + FOLLOW_TRAIL * i as f32) / mass;
+        let damping = spring.damping / mass;
+
+        let pos = transform.translation.truncate();
+        let mut follow_velocity = follow.as_ref().map_or(Vec2::ZERO, |follow| follow.0);
+        // Damping is integrated implicitly so heavy damping can't blow up.
+        follow_velocity =
+            (follow_velocity + (anchor + offset - pos) * stiffness * dt) / (1.0 + damping * dt);
+        transform.translation = (pos + follow_velocity * dt).extend(index);
+
+        match follow {
+            Some(mut follow) => follow.0 = follow_velocity,
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(FollowVelocity(follow_velocity));
+            }
+        }
+
+        transform.scale = transform.scale.lerp(CARD_SIZE * 1.2, 0.1);
+        commands
+            .entity(entity)
+            .insert((dragging, FanSlot(i, count)));
+
+        if time.delta_seconds() > 0.0 {
+            let moved = (transform.translation - previous).truncate() / time.delta_seconds();
+            let smoothed = velocity.map_or(moved, |velocity| velocity.lerp(moved, 0.5));
+            commands.entity(entity).insert(DragVelocity(smoothed));
+        }
+    }
+}
+
+/// Ghost of where the carried stack will land.
+fn show_drop_ghost(
+    carried: Query<(), SelectedCard>,
+    world_cursor: Res<WordCursor>,
+    regions: Res<GridRegions>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    if carried.is_empty() {
+        return;
+    }
+
+    gizmos.rect_2d(
+        align_grid(&regions, world_cursor.0),
+        0.0,
+        CARD_IMAGE_SIZE.as_vec2() * CARD_SIZE.truncate(),
+        contrast.outline(Color::rgba(1.0, 1.0, 1.0, 0.5)),
+    );
+}
+
+/// Spring-damper pulling each carried card to its place in the fan. Critically
+/// damped by default, so cards close in without overshooting.
+#[derive(Resource)]
+struct FollowSpring {
+    stiffness: f32,
+    damping: f32,
+}
+
+/// How much softer the follow spring gets per slot along the fan.
+const FOLLOW_TRAIL: f32 = 0.15;
+
+/// A carried card's velocity under the `FollowSpring`.
+#[derive(Component, Default)]
+struct FollowVelocity(Vec2);
+
+/// How heavy a card feels while carried: its mass on the `FollowSpring`, so
+/// heavier cards trail further behind. Cards without one weigh 1.
+#[derive(Component, Clone, Copy)]
+struct Weight(f32);
+
+impl Weight {
+    const MIN: f32 = 0.25;
+    const MAX: f32 = 8.0;
+
+    /// The weight clamped so the spring stays stable and no card freezes in place.
+    fn get(self) -> f32 {
+        self.0.clamp(Self::MIN, Self::MAX)
+    }
+}
+
+impl Default for Weight {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Whether carried cards jump between grid cells as they move, or follow the
+/// cursor freely and only snap once released.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+enum SnapMode {
+    Live,
+    #[default]
+    OnRelease,
+}
+
+fn toggle_snap_mode(keys: Res<Input<KeyCode>>, mut snap_mode: ResMut<SnapMode>) {
+    if keys.just_pressed(KeyCode::F10) {
+        *snap_mode = match *snap_mode {
+            SnapMode::Live => SnapMode::OnRelease,
+            SnapMode::OnRelease => SnapMode::Live,
+        };
+    }
+}
+
+/// When a card was picked up, relative to the others being carried.
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct DragOrder(u32);
+
+/// The `DragOrder` given to the next card picked up.
+#[derive(Resource, Default)]
+struct NextDragOrder(u32);
+
+/// A card's place in the fan it was dragged in, and how many cards that fan
+/// holds. Kept after the drop so the fan settles in the same arc it was
+/// carried in, ordered the same way.
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct FanSlot(usize, usize);
+
+/// Offset between neighbouring cards of a fan with room to spare.
+const FAN_STEP: f32 = 10.0;
+
+/// How far, on each axis, the last card of a fan may sit from the first.
+#[derive(Resource)]
+struct MaxPileExtent(f32);
+
+/// Where the card in `slot` of a `count` card fan sits relative to the first.
+/// Fans that would reach past `max_extent` are squashed evenly to fit.
+fn pile_offset_for(slot: usize, count: usize, max_extent: f32) -> Vec2 {
+    let gaps = count.saturating_sub(1).max(1) as f32;
+    // Scaled before dividing, so the last card of a squashed fan lands exactly
+    // on `max_extent` rather than a rounding error past it.
+    let slot = slot as f32;
+    Vec2::splat((slot * FAN_STEP).min(slot * max_extent / gaps))
+}
+
+/// Cards released in here are discarded.
+#[derive(Resource, Deref)]
+struct DiscardZone(Rect);
+
+/// Releasing a card faster than `speed_threshold` discards it if its
+/// extrapolated landing point is inside the `DiscardZone`.
+#[derive(Resource)]
+struct FlingDiscard {
+    speed_threshold: f32,
+}
+
+/// How far ahead, in seconds, a flung card's landing point is predicted.
+const FLING_LOOKAHEAD: f32 = 0.25;
+
+const DISCARD_ZONE_SIZE: Vec2 = Vec2::new(120.0, 170.0);
+const DISCARD_ZONE_MARGIN: f32 = 20.0;
+
+type DiscardCameraQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Camera,
+        &'static GlobalTransform,
+        Ref<'static, Transform>,
+        Ref<'static, OrthographicProjection>,
+    ),
+    With<CardsCamera>,
+>;
+
+/// Keeps the discard zone in the bottom right corner of the window as it
+/// resizes and as the cards camera pans, zooms, resets, follows or rotates.
+/// The size and margin are in screen pixels, so the zone looks the same at any
+/// zoom. Runs after the camera's matrices are updated, so the corner is mapped
+/// through the view the frame is drawn with.
+fn anchor_discard_zone(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window>,
+    camera_query: DiscardCameraQuery,
+    mut zone: ResMut<DiscardZone>,
+) {
+    let (Ok(window), Ok((camera, camera_transform, transform, projection))) =
+        (windows.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+
+    let window_changed = resized.read().count() > 0;
+    if !window_changed && !transform.is_changed() && !projection.is_changed() && !zone.is_added() {
+        return;
+    }
+
+    let window_size = vec2(window.width(), window.height());
+    let screen_center = window_size - (DISCARD_ZONE_SIZE / 2.0 + DISCARD_ZONE_MARGIN);
+    let Some(center) = camera.viewport_to_world_2d(camera_transform, screen_center) else {
+        return;
+    };
+
+    zone.0 = Rect::from_center_size(center, DISCARD_ZONE_SIZE * projection.scale);
+}
+
+fn show_discard_zone(zone: Res<DiscardZone>, contrast: Res<HighContrast>, mut gizmos: Gizmos) {
+    gizmos.rect_2d(
+        zone.center(),
+        0.0,
+        zone.size(),
+        contrast.outline(Color::DARK_GRAY),
+    );
+}
+
+/// The most recently discarded card, so it can be brought back once.
+#[derive(Resource, Default)]
+struct LastDiscard(Option<DiscardedCard>);
+
+struct DiscardedCard {
+    card: CardData,
+    face: Face,
+    position: Vec2,
+    tags: Tags,
+}
+
+type DiscardQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        &'static DragVelocity,
+        &'static DragOrigin,
+        &'static CardData,
+        &'static Face,
+        &'static Tags,
+        SelectedCard,
+    ),
+>;
+
+fn discard_dropped(
+    query: DiscardQuery,
+    input: DragInput,
+    zone: Res<DiscardZone>,
+    fling: Res<FlingDiscard>,
+    delay: Res<DiscardDelay>,
+    mut last_discard: ResMut<LastDiscard>,
+    mut commands: Commands,
+) {
+    if !input.drop_pressed(!query.is_empty()) {
+        return;
+    }
+
+    for (entity, transform, velocity, origin, card, face, tags, _) in &query {
+        let pos = transform.translation.truncate();
+        let predicted = pos + velocity.0 * FLING_LOOKAHEAD;
+        let flung = velocity.length() >= fling.speed_threshold && zone.contains(predicted);
+
+        if zone.contains(pos) || flung {
+            commands
+                .entity(entity)
+                .remove::<(Selected, Dragging, FanSlot)>()
+                .insert(Disappearing(Timer::from_seconds(delay.0, TimerMode::Once)));
+            last_discard.0 = Some(DiscardedCard {
+                card: *card,
+                face: *face,
+                position: origin.0,
+                tags: tags.clone(),
+            });
+        }
+    }
+}
+
+/// Seconds a discarded card takes to fade out before it is despawned.
+#[derive(Resource)]
+struct DiscardDelay(f32);
+
+/// A card on its way out of play: it fades over the timer (unless it belongs to
+/// a completed set leaving some other way), then is despawned. It can't be
+/// picked up or hovered meanwhile.
+#[derive(Component, Deref, DerefMut)]
+struct Disappearing(Timer);
+
+type DisappearingQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Disappearing,
+        &'static mut Sprite,
+        Option<&'static Children>,
+        Option<&'static Completing>,
+    ),
+>;
+
+fn fade_disappearing(
+    mut query: DisappearingQuery,
+    mut texts: Query<&mut Text>,
+    time: Res<Time>,
+    mut despawn: EventWriter<DespawnCard>,
+) {
+    for (entity, mut disappearing, mut sprite, children, completing) in &mut query {
+        disappearing.tick(time.delta());
+        if disappearing.finished() {
+            despawn.send(DespawnCard(entity));
+            continue;
+        }
+
+        // Completed sets flying off or shrinking stay opaque.
+        if completing.is_some_and(|completing| completing.animation != CompleteAnimation::FadeOut) {
+            continue;
+        }
+
+        let alpha = disappearing.percent_left();
+        sprite.color.set_a(alpha);
+        for child in children.iter().flat_map(|children| children.iter()) {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                for section in &mut text.sections {
+                    section.style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+/// Faint card backs left behind a card moving faster than `speed_threshold`,
+/// one every `interval` seconds.
+#[derive(Resource)]
+struct MotionTrails {
+    enabled: bool,
+    speed_threshold: f32,
+    interval: f32,
+}
+
+/// How a card's trail looks: at most `max_ghosts` at once, each fading out
+/// over `fade` seconds.
+#[derive(Component)]
+struct MotionTrail {
+    max_ghosts: usize,
+    fade: f32,
+}
+
+impl Default for MotionTrail {
+    fn default() -> Self {
+        Self {
+            max_ghosts: 6,
+            fade: 0.25,
+        }
+    }
+}
+
+/// Opacity of a ghost when it is left behind.
+const TRAIL_ALPHA: f32 = 0.35;
+
+/// A fading copy of where `card` just was. It isn't a `Card`, so nothing can
+/// hover or pick it up.
+#[derive(Component)]
+struct TrailGhost {
+    card: Entity,
+    life: Timer,
+}
+
+fn toggle_motion_trails(
+    keys: Res<Input<KeyCode>>,
+    mut trails: ResMut<MotionTrails>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keys.just_pressed(KeyCode::Z) {
+        trails.enabled = !trails.enabled;
+        toasts.send(Toast(if trails.enabled {
+            "Fast cards leave a trail".to_owned()
+        } else {
+            "Motion trails off".to_owned()
+        }));
+    }
+}
+
+/// The card back in the current `BackTheme`, or the `HighContrast` one.
+#[derive(SystemParam)]
+struct BackTexture<'w> {
+    assets: Res<'w, CardAssets>,
+    theme: Res<'w, BackTheme>,
+    contrast: Res<'w, HighContrast>,
+}
+
+impl BackTexture<'_> {
+    fn get(&self) -> Handle<Image> {
+        self.assets
+            .contrasted(&self.assets.back(*self.theme), self.contrast.0, *self.theme)
+    }
+}
+
+fn spawn_trail_ghosts(
+    cards: Query<(Entity, &Transform, &MotionTrail, Option<&RenderLayers>), With<Card>>,
+    ghosts: Query<&TrailGhost>,
+    trails: Res<MotionTrails>,
+    back: BackTexture,
+    time: Res<Time>,
+    // Where each card was last frame, and how long since its last ghost.
+    mut tracked: Local<HashMap<Entity, (Vec3, f32)>>,
+    mut commands: Commands,
+) {
+    if !trails.enabled {
+        tracked.clear();
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    tracked.retain(|entity, _| cards.contains(*entity));
+
+    for (entity, transform, trail, layers) in &cards {
+        let Some((last, since)) = tracked.get_mut(&entity) else {
+            tracked.insert(entity, (transform.translation, 0.0));
+            continue;
+        };
+        let previous = std::mem::replace(last, transform.translation);
+        *since += dt;
+
+        let speed = previous
+            .truncate()
+            .distance(transform.translation.truncate())
+            / dt.max(f32::EPSILON);
+        if speed < trails.speed_threshold || *since < trails.interval {
+            continue;
+        }
+        let live = ghosts.iter().filter(|ghost| ghost.card == entity).count();
+        if live >= trail.max_ghosts {
+            continue;
+        }
+        *since = 0.0;
+
+        let mut ghost = commands.spawn((
+            TrailGhost {
+                card: entity,
+                life: Timer::from_seconds(trail.fade, TimerMode::Once),
+            },
+            SpriteBundle {
+                texture: back.get(),
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, TRAIL_ALPHA),
+                    ..default()
+                },
+                // Just under the card, so it never hides what left it.
+                transform: Transform::from_translation(previous - Vec3::Z * 0.5)
+                    .with_rotation(transform.rotation)
+                    .with_scale(transform.scale),
+                ..default()
+            },
+        ));
+        if let Some(layers) = layers {
+            ghost.insert(*layers);
+        }
+    }
+}
+
+fn fade_trail_ghosts(
+    mut ghosts: Query<(Entity, &mut TrailGhost, &mut Sprite)>,
+    cards: Query<(), With<Card>>,
+    trails: Res<MotionTrails>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut ghost, mut sprite) in &mut ghosts {
+        ghost.life.tick(time.delta());
+        // Ghosts go with their card, or straight away when trails are turned off.
+        if ghost.life.finished() || !trails.enabled || !cards.contains(ghost.card) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        sprite.color.set_a(TRAIL_ALPHA * ghost.life.percent_left());
+    }
+}
+
+/// U brings back the last discarded card, counted against `MaxCards` like any
+/// other spawn. At the limit it stays discarded, so it can be recalled later.
+fn recall_discard(
+    keys: Res<Input<KeyCode>>,
+    card_assets: Res<CardAssets>,
+    limit: CardLimit,
+    mut last_discard: ResMut<LastDiscard>,
+    mut spawned: EventWriter<CardSpawned>,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::U) || last_discard.0.is_none() {
+        return;
+    }
+
+    if limit.on_board() >= limit.max.0 {
+        toasts.send(limit.reached_toast());
+        return;
+    }
+
+    let Some(discarded) = last_discard.0.take() else {
+        return;
+    };
+
+    let entity = spawn_card_at(
+        &mut commands,
+        &card_assets,
+        discarded.position,
+        discarded.card,
+        discarded.face,
+    );
+    commands.entity(entity).insert(discarded.tags);
+    spawned.send(CardSpawned);
+}
+
+/// Cards in each pile, ordered bottom to top by when they joined the pile.
+///
+/// Keyed by `Entity` rather than `CardId`: a card is indexed the frame it
+/// lands, before `CardIdPlugin` gives it an id in `PostUpdate`, and the index
+/// is never written out. Saves map entities to ids as they are written.
+#[derive(Resource, Default, Deref)]
+struct PileIndex(HashMap<Pile, Vec<Entity>>);
+
+/// The most cards a pile is expected to hold: every card of two decks.
+const MAX_PILE_CARDS: usize = 2 * STANDARD_DECK_SIZE;
+
+/// Depth between the cards of a pile. A full pile rises by less than one world
+/// unit of `ysort` depth, so it never draws over a pile lower on screen.
+const PILE_Z_STEP: f32 = YSORT_SCALE / MAX_PILE_CARDS as f32;
+
+fn index_piles(
+    mut index: ResMut<PileIndex>,
+    added: Query<(Entity, &Pile, Option<&FanSlot>), Added<Pile>>,
+    loose: Query<(Entity, &LooseCell)>,
+    mut removed: RemovedComponents<Pile>,
+    mut commands: Commands,
+) {
+    if added.is_empty() && removed.is_empty() {
+        return;
+    }
+
+    for entity in removed.read() {
+        for cards in index.0.values_mut() {
+            cards.retain(|card| *card != entity);
+        }
+    }
+    index.0.retain(|_, cards| !cards.is_empty());
+
+    // Cards of a fan landing together stack in fan order.
+    let mut added = added.iter().collect::<Vec<_>>();
+    added.sort_by_key(|(_, _, slot)| slot.copied());
+
+    for (entity, pile, _) in added {
+        let cards = index.0.entry(*pile).or_default();
+
+        // A card landing on a collapsed single stacks on top of it again.
+        if cards.is_empty() {
+            for (card, _) in loose.iter().filter(|(_, cell)| cell.0 == *pile) {
+                cards.push(card);
+                commands.entity(card).remove::<LooseCell>().insert(*pile);
+            }
+        }
+
+        if !cards.contains(&entity) {
+            cards.push(entity);
+        }
+    }
+}
+
+/// Areas whose piles build up by suit from the Ace, kept in rank order.
+#[derive(Resource, Deref)]
+struct Foundations(Vec<Rect>);
+
+impl Foundations {
+    /// Four cells along the top of the right side of the board.
+    fn top_right(regions: &GridRegions) -> Self {
+        Self(
+            (0..4)
+                .map(|i| {
+                    let point = vec2(80.0 + i as f32 * 150.0, 250.0);
+                    let grid = regions.config_at(point);
+                    Rect::from_center_size(grid.snap(point), grid.cell_size)
+                })
+                .collect(),
+        )
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        self.iter().any(|zone| zone.contains(point))
+    }
+}
+
+fn show_foundations(
+    foundations: Res<Foundations>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    for zone in foundations.iter() {
+        gizmos.rect_2d(
+            zone.center(),
+            0.0,
+            zone.size(),
+            contrast.outline(Color::GOLD),
+        );
+    }
+}
+
+/// The kinds of area on the board, each with its own rules.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum ZoneKind {
+    Tableau,
+    Foundation,
+}
+
+impl ZoneKind {
+    fn at(point: Vec2, foundations: &Foundations) -> Self {
+        if foundations.contains(point) {
+            ZoneKind::Foundation
+        } else {
+            ZoneKind::Tableau
+        }
+    }
+}
+
+/// A dim placeholder where a pile belongs, shown while no card is on it.
+#[derive(Component)]
+struct PileSlot {
+    cell: (i32, i32),
+    kind: ZoneKind,
+}
+
+/// Which kinds of empty `PileSlot` are marked.
+#[derive(Resource)]
+struct PileMarkers {
+    tableau: bool,
+    foundation: bool,
+}
+
+impl PileMarkers {
+    fn shows(&self, kind: ZoneKind) -> bool {
+        match kind {
+            ZoneKind::Tableau => self.tableau,
+            ZoneKind::Foundation => self.foundation,
+        }
+    }
+}
+
+fn spawn_pile_slots(regions: &GridRegions, foundations: &Foundations, commands: &mut Commands) {
+    let tableau =
+        (0..TABLEAU_PILES).map(|pile| (tableau_pile_pos(regions, pile), ZoneKind::Tableau));
+    let foundation = foundations
+        .iter()
+        .map(|zone| (zone.center(), ZoneKind::Foundation));
+
+    for (pos, kind) in tableau.chain(foundation) {
+        let cell = Pile::new(pos);
+        // Foundations are tinted like their outline.
+        let tint = match kind {
+            ZoneKind::Tableau => Color::WHITE,
+            ZoneKind::Foundation => Color::GOLD,
+        };
+        commands.spawn((
+            PileSlot {
+                cell: (cell.0, cell.1),
+                kind,
+            },
+            // Not a `Card`, and on the background layer, so it is only ever drawn.
+            RenderLayers::layer(BACKGROUND_LAYER),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: tint.with_a(0.08),
+                    custom_size: Some(CARD_IMAGE_SIZE.as_vec2() * CARD_SIZE.truncate()),
+                    ..default()
+                },
+                // Below every card.
+                transform: Transform::from_translation(pos.extend(-1.0)),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn show_pile_slots(
+    mut slots: Query<(&PileSlot, &mut Visibility)>,
+    index: Res<PileIndex>,
+    markers: Res<PileMarkers>,
+) {
+    for (slot, mut visibility) in &mut slots {
+        let occupied = index
+            .get(&Pile(slot.cell.0, slot.cell.1))
+            .is_some_and(|cards| !cards.is_empty());
+        let shown = markers.shows(slot.kind) && !occupied;
+        *visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// When cards turn face up by themselves.
+#[derive(Clone, Copy, Default)]
+struct AutoFlip {
+    /// A face-down card dropped onto a pile turns face up.
+    on_drop: bool,
+    /// Taking the top card off a pile turns the card it uncovers face up.
+    on_expose: bool,
+}
+
+#[derive(Resource)]
+struct AutoFlipOnDrop(HashMap<ZoneKind, AutoFlip>);
+
+impl Default for AutoFlipOnDrop {
+    fn default() -> Self {
+        Self(
+            [
+                (
+                    ZoneKind::Tableau,
+                    AutoFlip {
+                        on_drop: true,
+                        on_expose: true,
+                    },
+                ),
+                (
+                    ZoneKind::Foundation,
+                    AutoFlip {
+                        on_drop: true,
+                        on_expose: true,
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+}
+
+impl AutoFlipOnDrop {
+    fn rule(&self, zone: ZoneKind) -> AutoFlip {
+        self.0.get(&zone).copied().unwrap_or_default()
+    }
+}
+
+fn auto_flip(
+    rules: Res<AutoFlipOnDrop>,
+    foundations: Res<Foundations>,
+    index: Res<PileIndex>,
+    cards: Query<(&Transform, &Face), Without<FlipAnim>>,
+    mut dropped: EventReader<CardDropped>,
+    mut tops: Local<HashMap<Pile, (Entity, usize)>>,
+    mut commands: Commands,
+) {
+    for CardDropped(entity) in dropped.read() {
+        let Ok((transform, face)) = cards.get(*entity) else {
+            continue;
+        };
+
+        let zone = ZoneKind::at(transform.translation.truncate(), &foundations);
+        if *face == Face::Down && rules.rule(zone).on_drop {
+            request_flip(*entity, *face, &mut commands);
+        }
+    }
+
+    if !index.is_changed() {
+        return;
+    }
+
+    for (pile, pile_cards) in index.iter() {
+        let Some(&top) = pile_cards.last() else {
+            continue;
+        };
+
+        let exposed = tops
+            .get(pile)
+            .is_some_and(|(old_top, len)| *old_top != top && *len > pile_cards.len());
+        let zone = ZoneKind::at(vec2(pile.0 as f32, pile.1 as f32), &foundations);
+
+        if exposed && rules.rule(zone).on_expose {
+            if let Ok((_, Face::Down)) = cards.get(top) {
+                request_flip(top, Face::Down, &mut commands);
+            }
+        }
+    }
+
+    *tops = index
+        .iter()
+        .filter_map(|(pile, pile_cards)| Some((*pile, (*pile_cards.last()?, pile_cards.len()))))
+        .collect();
+}
+
+/// Whether `card` landing on foundation `pile` continues its run, considering
+/// everything `inbound` to the same pile this drop. Accepted cards are stacked
+/// by rank by `sort_foundation_piles`, whatever order they land in.
+fn foundation_accepts(
+    pile: Pile,
+    card: Option<&CardData>,
+    index: &PileIndex,
+    piled: &Query<&CardData, With<Pile>>,
+    inbound: &HashMap<Pile, Vec<CardData>>,
+) -> bool {
+    let Some(card) = card else {
+        return false;
+    };
+
+    let resting = index
+        .get(&pile)
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| piled.get(*entity).ok().copied())
+        .collect::<Vec<_>>();
+    let inbound = inbound.get(&pile).map_or(&[][..], Vec::as_slice);
+
+    foundation_run(&resting, inbound).contains(card)
+}
+
+/// Restacks foundation piles bottom to top by rank.
+fn sort_foundation_piles(
+    mut index: ResMut<PileIndex>,
+    foundations: Res<Foundations>,
+    cards: Query<&CardData>,
+) {
+    if !index.is_changed() {
+        return;
+    }
+
+    let rank = |entity: &Entity| cards.get(*entity).map(|card| card.rank).ok();
+    let unsorted = index
+        .iter()
+        .filter(|(pile, _)| foundations.contains(vec2(pile.0 as f32, pile.1 as f32)))
+        .filter(|(_, entities)| {
+            entities
+                .windows(2)
+                .any(|pair| rank(&pair[0]) > rank(&pair[1]))
+        })
+        .map(|(pile, _)| *pile)
+        .collect::<Vec<_>>();
+
+    for pile in unsorted {
+        if let Some(entities) = index.0.get_mut(&pile) {
+            entities.sort_by_key(&rank);
+        }
+    }
+}
+
+/// A foundation built all the way from Ace to King, bottom to top.
+#[derive(Event)]
+struct SetCompleted(Vec<Entity>);
+
+fn detect_completed_sets(
+    index: Res<PileIndex>,
+    foundations: Res<Foundations>,
+    cards: Query<&CardData>,
+    mut completed: EventWriter<SetCompleted>,
+) {
+    if !index.is_changed() {
+        return;
+    }
+
+    for (pile, entities) in index.iter() {
+        if !foundations.contains(vec2(pile.0 as f32, pile.1 as f32)) {
+            continue;
+        }
+
+        let ranks = entities
+            .iter()
+            .map(|entity| cards.get(*entity).map(|card| card.rank).ok())
+            .collect::<Vec<_>>();
+        if ranks.iter().copied().eq(Rank::ALL.into_iter().map(Some)) {
+            completed.send(SetCompleted(entities.clone()));
+        }
+    }
+}
+
+/// A card that has already scored for reaching a foundation.
+#[derive(Component)]
+struct FoundationScored;
+
+/// Turns drops onto foundations and completed sets into `ScoreEvent`s. Each
+/// card scores for a foundation once, so lifting it off and dropping it back
+/// earns nothing.
+fn report_score_events(
+    foundations: Res<Foundations>,
+    cards: Query<(&Transform, Has<FoundationScored>)>,
+    mut dropped: EventReader<CardDropped>,
+    mut completed: EventReader<SetCompleted>,
+    mut score: EventWriter<ScoreEvent>,
+    mut commands: Commands,
+) {
+    for CardDropped(entity) in dropped.read() {
+        let Ok((transform, scored)) = cards.get(*entity) else {
+            continue;
+        };
+
+        if !scored
+            && ZoneKind::at(transform.translation.truncate(), &foundations) == ZoneKind::Foundation
+        {
+            score.send(ScoreEvent::CardToFoundation);
+            commands.entity(*entity).insert(FoundationScored);
+        }
+    }
+
+    for _ in completed.read() {
+        score.send(ScoreEvent::SetCompleted);
+    }
+}
+
+/// Where completed sets are laid out to show them off, one card every `spacing`
+/// along x from `origin`.
+#[derive(Resource)]
+struct DisplayRow {
+    origin: Vec2,
+    spacing: f32,
+}
+
+impl DisplayRow {
+    fn slot(&self, n: usize) -> Vec2 {
+        self.origin + Vec2::X * self.spacing * n as f32
+    }
+}
+
+/// A card moved to the `DisplayRow`. It can no longer be picked up.
+#[derive(Component)]
+struct Displayed(usize);
+
+/// How the cards of a completed set leave their foundation.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompleteAnimation {
+    /// Into the `DisplayRow`, where they stay.
+    #[default]
+    Display,
+    /// Thrown off the top of the board.
+    FlyOff,
+    FadeOut,
+    Shrink,
+}
+
+/// Seconds the cards of a completed set take to leave the board.
+const COMPLETE_DURATION: f32 = 0.6;
+
+/// A card of a completed set on its way out, from where it lay at `from`.
+/// Despawned by `fade_disappearing` once its `Disappearing` runs out.
+#[derive(Component)]
+struct Completing {
+    animation: CompleteAnimation,
+    from: Vec2,
+}
+
+/// Takes the cards of each completed set off their foundation, into the next
+/// slots of the display row or away as `CompleteAnimation` says. The set is
+/// scored by `report_score_events` as soon as it completes, before any card
+/// is removed.
+fn clear_completed_set(
+    mut completed: EventReader<SetCompleted>,
+    animation: Res<CompleteAnimation>,
+    cards: Query<&Transform>,
+    displayed: Query<&Displayed>,
+    mut commands: Commands,
+) {
+    let mut next = displayed.iter().count();
+
+    for SetCompleted(entities) in completed.read() {
+        for entity in entities {
+            let Ok(transform) = cards.get(*entity) else {
+                continue;
+            };
+            let mut card = commands.entity(*entity);
+            card.remove::<(Pile, LooseCell, FanSlot, Dragging, HoverLift)>();
+
+            match *animation {
+                CompleteAnimation::Display => {
+                    card.insert(Displayed(next));
+                    next += 1;
+                }
+                animation => {
+                    card.insert((
+                        Completing {
+                            animation,
+                            from: transform.translation.truncate(),
+                        },
+                        Disappearing(Timer::from_seconds(COMPLETE_DURATION, TimerMode::Once)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Flies or shrinks the cards of completed sets away. Fading out is left to
+/// `fade_disappearing`.
+fn animate_completing(
+    mut query: Query<(&mut Transform, &Completing, &Disappearing)>,
+    board: Res<BoardBounds>,
+) {
+    for (mut transform, completing, disappearing) in &mut query {
+        let t = disappearing.percent();
+        match completing.animation {
+            CompleteAnimation::FlyOff => {
+                // Speeding up until a card's height clear of the top edge.
+                let card_height = CARD_IMAGE_SIZE.y as f32 * CARD_SIZE.y;
+                let to = vec2(completing.from.x, board.0.max.y + card_height);
+                let pos = completing.from.lerp(to, t * t);
+                transform.translation = pos.extend(transform.translation.z);
+            }
+            CompleteAnimation::Shrink => {
+                transform.scale = (CARD_SIZE.truncate() * (1.0 - t)).extend(CARD_SIZE.z);
+            }
+            CompleteAnimation::Display | CompleteAnimation::FadeOut => {}
+        }
+    }
+}
+
+fn animate_display_row(
+    mut query: Query<(&mut Transform, &Displayed)>,
+    row: Res<DisplayRow>,
+    time: Res<Time>,
+) {
+    for (mut transform, Displayed(slot)) in &mut query {
+        let target = row.slot(*slot);
+        let t = (time.delta_seconds() * 8.0).min(1.0);
+        let pos = transform.translation.truncate().lerp(target, t);
+        // Later cards overlap the ones before them.
+        transform.translation = pos.extend(*slot as f32 * PILE_Z_STEP);
+    }
+}
+
+/// Asks what is in the pile of the grid cell containing this point. Answered
+/// with a `PileContents` once the pile index is up to date.
+#[derive(Event)]
+struct QueryPile(Vec2);
+
+/// The cards of a queried pile, bottom to top. Empty if the cell has no pile.
+#[derive(Event)]
+struct PileContents {
+    cell: Vec2,
+    cards: Vec<CardId>,
+}
+
+fn answer_pile_queries(
+    mut queries: EventReader<QueryPile>,
+    index: Res<PileIndex>,
+    regions: Res<GridRegions>,
+    ids: Query<&CardId>,
+    mut contents: EventWriter<PileContents>,
+) {
+    for QueryPile(point) in queries.read() {
+        let cell = align_grid(&regions, *point);
+        let cards = index
+            .get(&Pile::new(cell))
+            .into_iter()
+            .flatten()
+            .filter_map(|card| ids.get(*card).ok().copied())
+            .collect();
+
+        contents.send(PileContents { cell, cards });
+    }
+}
+
+fn print_pile_contents(
+    mut contents: EventReader<PileContents>,
+    mut output: EventWriter<ConsoleOutput>,
+) {
+    for PileContents { cell, cards } in contents.read() {
+        let ids = cards.iter().map(|id| id.0.to_string()).collect::<Vec<_>>();
+        output.send(ConsoleOutput(if ids.is_empty() {
+            format!("no pile at {cell}")
+        } else {
+            format!("pile at {cell}: {}", ids.join(" "))
+        }));
+    }
+}
+
+/// Whether a pile left with a single card stops being a pile.
+#[derive(Resource)]
+struct AutoCollapseSingles(bool);
+
+/// Where a collapsed single card rests, so a card dropped onto it still stacks.
+#[derive(Component)]
+struct LooseCell(Pile);
+
+fn toggle_auto_collapse(keys: Res<Input<KeyCode>>, mut auto_collapse: ResMut<AutoCollapseSingles>) {
+    if keys.just_pressed(KeyCode::F8) {
+        auto_collapse.0 = !auto_collapse.0;
+    }
+}
+
+/// Strips `Pile` from the last card of a pile that has shrunk to one card,
+/// leaving it a loose card again.
+fn collapse_single_piles(
+    auto_collapse: Res<AutoCollapseSingles>,
+    index: Res<PileIndex>,
+    mut counts: Local<HashMap<Pile, usize>>,
+    mut commands: Commands,
+) {
+    if !index.is_changed() {
+        return;
+    }
+
+    for (pile, cards) in index.iter() {
+        let shrunk = counts.get(pile).is_some_and(|count| *count > cards.len());
+        if auto_collapse.0 && shrunk && cards.len() == 1 {
+            commands
+                .entity(cards[0])
+                .remove::<Pile>()
+                .insert(LooseCell(*pile));
+        }
+    }
+
+    *counts = index
+        .iter()
+        .map(|(pile, cards)| (*pile, cards.len()))
+        .collect();
+}
+
+/// Whether two resting loose cards that overlap stack into a pile, when the
+/// `PlacementRule` lets the upper one go on the lower.
+#[derive(Resource)]
+struct AutoStackOnContact(bool);
+
+fn toggle_auto_stack(
+    keys: Res<Input<KeyCode>>,
+    mut auto_stack: ResMut<AutoStackOnContact>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keys.just_pressed(KeyCode::M) {
+        auto_stack.0 = !auto_stack.0;
+        toasts.send(Toast(if auto_stack.0 {
+            "Touching cards stack".to_owned()
+        } else {
+            "Touching cards stay apart".to_owned()
+        }));
+    }
+}
+
+type LooseCardQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Bounds,
+        &'static Transform,
+        &'static CardData,
+        Option<&'static LooseCell>,
+    ),
+    (
+        With<Card>,
+        Without<Pile>,
+        Without<Dragging>,
+        Without<Selected>,
+        Without<Compacting>,
+        Without<Inert>,
+        // Laid out, on their way in or on their way out, not resting.
+        Without<Displayed>,
+        Without<QueuedDeal>,
+        Without<DealAnimation>,
+        Without<Disappearing>,
+    ),
+>;
+
+/// The lower card becomes a pile where it rests and the upper one settles onto
+/// it. Neither is loose afterwards, so a pair is only ever merged once; nothing
+/// moves loose cards apart again, so there is nothing for this to fight with.
+fn auto_stack_on_contact(
+    auto_stack: Res<AutoStackOnContact>,
+    rule: Res<PlacementRule>,
+    regions: Res<GridRegions>,
+    loose: LooseCardQuery,
+    mut commands: Commands,
+) {
+    if !auto_stack.0 {
+        return;
+    }
+
+    let mut cards = loose.iter().collect::<Vec<_>>();
+    cards.sort_by(|(_, _, a, ..), (_, _, b, ..)| a.translation.z.total_cmp(&b.translation.z));
+
+    let mut merged = HashSet::new();
+    for (i, (below, below_bounds, below_transform, below_card, cell)) in cards.iter().enumerate() {
+        if merged.contains(below) {
+            continue;
+        }
+
+        let above = cards[i + 1..].iter().find(|(above, bounds, _, card, _)| {
+            !merged.contains(above)
+                && !below_bounds.0.intersect(bounds.0).is_empty()
+                && rule.allows(below_card, card)
+        });
+        let Some((above, ..)) = above else {
+            continue;
+        };
+
+        let base = match cell {
+            Some(LooseCell(pile)) => align_grid(&regions, vec2(pile.0 as f32, pile.1 as f32)),
+            None => align_grid(&regions, below_transform.translation.truncate()),
+        };
+
+        commands
+            .entity(*below)
+            .remove::<LooseCell>()
+            .insert((Pile::new(base), FanSlot(0, 2)));
+        commands
+            .entity(*above)
+            .insert((Dragging(base), FanSlot(1, 2)));
+        merged.insert(*below);
+        merged.insert(*above);
+    }
+}
+
+/// Depth per world unit of height. Small enough that the whole board stays
+/// well below dragged cards, which start at z = 1.
+const YSORT_SCALE: f32 = 0.0005;
+
+fn ysort_z(y: f32) -> f32 {
+    -y * YSORT_SCALE
+}
+
+type SortedCardQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Transform,
+    (
+        With<Card>,
+        Without<Selected>,
+        Without<Dragging>,
+        Without<Player>,
+    ),
+>;
+
+/// Draws whatever is lower on screen in front, so the player can walk both in
+/// front of and behind resting cards. Piles stack upwards from their sorted depth.
+fn ysort(
+    index: Res<PileIndex>,
+    mut cards: SortedCardQuery,
+    loose: Query<(Entity, &LooseCell)>,
+    mut players: Query<&mut Transform, (With<Player>, Without<Card>)>,
+) {
+    for (pile, pile_cards) in index.iter() {
+        for (i, card) in pile_cards.iter().enumerate() {
+            if let Ok(mut transform) = cards.get_mut(*card) {
+                transform.translation.z = ysort_z(pile.1 as f32) + i as f32 * PILE_Z_STEP;
+            }
+        }
+    }
+
+    for (card, LooseCell(pile)) in &loose {
+        if let Ok(mut transform) = cards.get_mut(card) {
+            transform.translation.z = ysort_z(pile.1 as f32);
+        }
+    }
+
+    for mut transform in &mut players {
+        transform.translation.z = ysort_z(transform.translation.y);
+    }
+}
+
+/// Tidies every pile: each card slides back to its exact fan offset from the
+/// pile's cell, in pile order, undoing whatever offsets separate drops left.
+#[derive(Event)]
+struct CompactPiles;
+
+/// A resting card sliding to its tidy place in its pile.
+#[derive(Component)]
+struct Compacting {
+    from: Vec2,
+    to: Vec2,
+    t: f32,
+}
+
+fn compact_piles(
+    mut events: EventReader<CompactPiles>,
+    index: Res<PileIndex>,
+    regions: Res<GridRegions>,
+    foundations: Res<Foundations>,
+    extent: Res<MaxPileExtent>,
+    cards: Query<&Transform, UnselectedCard>,
+    mut commands: Commands,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    for (pile, pile_cards) in index.iter() {
+        let base = align_grid(&regions, vec2(pile.0 as f32, pile.1 as f32));
+        let on_foundation = foundations.contains(base);
+        let count = pile_cards.len();
+
+        for (slot, card) in pile_cards.iter().enumerate() {
+            let Ok(transform) = cards.get(*card) else {
+                continue;
+            };
+
+            // Foundations stack squarely, as they do when cards land.
+            let to = if on_foundation {
+                base
+            } else {
+                commands.entity(*card).insert(FanSlot(slot, count));
+                base + pile_offset_for(slot, count, extent.0)
+            };
+
+            commands.entity(*card).insert(Compacting {
+                from: transform.translation.truncate(),
+                to,
+                t: 0.0,
+            });
+        }
+    }
+}
+
+/// Depth is left to `ysort`, which already restacks piles by index every frame.
+fn settle_compacted(
+    mut query: Query<(Entity, &mut Transform, &mut Compacting)>,
+    ease: Res<SettleEase>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut compacting) in &mut query {
+        compacting.t = (compacting.t + time.delta_seconds() / SETTLE_DURATION).min(1.0);
+        let pos = compacting
+            .from
+            .lerp(compacting.to, ease.0.apply(compacting.t));
+        transform.translation = pos.extend(transform.translation.z);
+
+        if compacting.t >= 1.0 {
+            commands.entity(entity).remove::<Compacting>();
+        }
+    }
+}
+
+/// Whether each pile's cards follow the active `PlacementRule`, cached until
+/// the pile index or rule changes. Piles of cards without `CardData` are left out.
+#[derive(Resource, Default)]
+struct PileValidity(HashMap<Pile, bool>);
+
+fn pile_validity(
+    index: Res<PileIndex>,
+    rule: Res<PlacementRule>,
+    cards: Query<Option<&CardData>, With<Card>>,
+    mut validity: ResMut<PileValidity>,
+) {
+    if !index.is_changed() && !rule.is_changed() {
+        return;
+    }
+
+    validity.0 = index
+        .iter()
+        .filter_map(|(pile, pile_cards)| {
+            let data = pile_cards
+                .iter()
+                .map(|card| cards.get(*card).ok().flatten())
+                .collect::<Option<Vec<_>>>()?;
+            Some((*pile, rule.is_valid_sequence(data)))
+        })
+        .collect();
+}
+
+/// Outlines each pile green or red by its cached `PileValidity`.
+fn show_pile_validity(
+    index: Res<PileIndex>,
+    validity: Res<PileValidity>,
+    cards: Query<&Bounds, With<Card>>,
+    contrast: Res<HighContrast>,
+    mut gizmos: Gizmos,
+) {
+    for (pile, valid) in &validity.0 {
+        let Some(bounds) = index
+            .get(pile)
+            .and_then(|pile_cards| pile_cards.first())
+            .and_then(|card| cards.get(*card).ok())
+        else {
+            continue;
+        };
+
+        let color = if *valid { Color::GREEN } else { Color::RED };
+        gizmos.rect_2d(
+            bounds.center(),
+            0.0,
+            bounds.size() + Vec2::splat(8.0),
+            contrast.outline(color),
+        );
+    }
+}
+
+/// Card count shown on the top card of a multi-card pile.
+#[derive(Component)]
+struct PileBadge;
+
+type BadgeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Parent,
+        &'static mut Text,
+        &'static mut RenderLayers,
+    ),
+    (With<PileBadge>, Without<Card>),
+>;
+
+/// Keeps a badge on the top card of every pile of two or more, on that card's
+/// render layer. Badges already in place only have their count rewritten; one
+/// is spawned or despawned only when a pile's top card changes or it crosses
+/// two cards.
+fn show_piles(
+    index: Res<PileIndex>,
+    layers: Query<&RenderLayers, With<Card>>,
+    mut badges: BadgeQuery,
+    mut commands: Commands,
+) {
+    for (_, parent, _, mut badge_layers) in &mut badges {
+        if let Ok(card_layers) = layers.get(parent.get()) {
+            badge_layers.set_if_neq(*card_layers);
+        }
+    }
+
+    if !index.is_changed() {
+        return;
+    }
+
+    let mut counts = index
+        .values()
+        .filter(|cards| cards.len() >= 2)
+        .filter_map(|cards| Some((*cards.last()?, cards.len())))
+        .collect::<HashMap<_, _>>();
+
+    for (badge, parent, mut text, _) in &mut badges {
+        match counts.remove(&parent.get()) {
+            Some(count) => {
+                let count = count.to_string();
+                if text.sections[0].value != count {
+                    text.sections[0].value = count;
+                }
+            }
+            None => commands.entity(badge).despawn_recursive(),
+        }
+    }
+
+    for (top, count) in counts {
+        let badge = commands
+            .spawn((
+                PileBadge,
+                layers
+                    .get(top)
+                    .copied()
+                    .unwrap_or(RenderLayers::layer(INTERACTIVE_LAYER)),
+                Text2dBundle {
+                    text: Text::from_section(
+                        count.to_string(),
+                        TextStyle {
+                            font_size: 60.0,
+                            color: Color::ORANGE_RED,
+                            ..default()
+                        },
+                    ),
+                    // Top right corner, in the card's unscaled texture space.
+                    transform: Transform::from_xyz(70.0, 115.0, 1.0),
+                    ..default()
+                },
+            ))
+            .id();
+        commands.entity(top).add_child(badge);
+    }
+}
+
+const STOCK_POS: Vec2 = Vec2::new(-560.0, 260.0);
+const SHUFFLE_GHOSTS: usize = 6;
+const SHUFFLE_DURATION: f32 = 0.8;
+
+/// Pixel size of the card textures.
+const CARD_IMAGE_SIZE: UVec2 = UVec2::new(195, 284);
+
+const CARD_FRONT_COLOR: [u8; 4] = [245, 245, 235, 255];
+
+/// A card-sized texture of a single colour.
+fn blank_card(rgba: [u8; 4]) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: CARD_IMAGE_SIZE.x,
+            height: CARD_IMAGE_SIZE.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &rgba,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Pure white faces and bright yellow backs, for `HighContrast`.
+const CONTRAST_FRONT_COLOR: [u8; 4] = [255, 255, 255, 255];
+const CONTRAST_BACK_COLOR: [u8; 4] = [255, 210, 0, 255];
+
+#[derive(Resource)]
+pub struct CardAssets {
+    front: Handle<Image>,
+    backs: [Handle<Image>; 3],
+    /// Used in place of `front` and every back while `HighContrast` is on.
+    contrast_front: Handle<Image>,
+    contrast_back: Handle<Image>,
+}
+
+impl CardAssets {
+    fn back(&self, theme: BackTheme) -> Handle<Image> {
+        self.backs[theme as usize].clone()
+    }
+
+    fn is_front(&self, texture: &Handle<Image>) -> bool {
+        *texture == self.front || *texture == self.contrast_front
+    }
+
+    /// The texture standing in for `texture` with high contrast `on` or off.
+    fn contrasted(&self, texture: &Handle<Image>, on: bool, theme: BackTheme) -> Handle<Image> {
+        match (self.is_front(texture), on) {
+            (true, true) => self.contrast_front.clone(),
+            (true, false) => self.front.clone(),
+            (false, true) => self.contrast_back.clone(),
+            (false, false) if *texture == self.contrast_back => self.back(theme),
+            (false, false) => texture.clone(),
+        }
+    }
+}
+
+type CardTextureQuery<'w, 's> =
+    Query<'w, 's, &'static mut Handle<Image>, Or<(With<Card>, With<Stock>)>>;
+
+/// Swaps cards and the stock between their normal and high-contrast textures.
+/// Any texture change is caught too, so cards spawned, flipped or re-skinned
+/// while the mode is on are drawn in high contrast as well.
+fn apply_contrast_textures(
+    contrast: Res<HighContrast>,
+    card_assets: Res<CardAssets>,
+    theme: Res<BackTheme>,
+    mut query: CardTextureQuery,
+) {
+    for mut texture in &mut query {
+        if contrast.is_changed() || texture.is_changed() {
+            let contrasted = card_assets.contrasted(&texture, contrast.0, *theme);
+            texture.set_if_neq(contrasted);
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+enum BackTheme {
+    #[default]
+    Blue,
+    Purple,
+    Red,
+}
+
+impl BackTheme {
+    const ALL: [BackTheme; 3] = [BackTheme::Blue, BackTheme::Purple, BackTheme::Red];
+
+    fn texture(self) -> &'static str {
+        match self {
+            BackTheme::Blue => "card_back_blue.png",
+            BackTheme::Purple => "card_back_purple.png",
+            BackTheme::Red => "card_back_red.png",
+        }
+    }
+
+    fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// Re-skins every face-down card (and the stock) with the next back theme.
+fn reskin_backs(
+    keys: Res<Input<KeyCode>>,
+    card_assets: Res<CardAssets>,
+    mut theme: ResMut<BackTheme>,
+    mut query: CardTextureQuery,
+) {
+    if !keys.just_pressed(KeyCode::T) {
+        return;
+    }
+
+    *theme = theme.next();
+    let back = card_assets.back(*theme);
+
+    for mut texture in &mut query {
+        if !card_assets.is_front(&texture) {
+            *texture = back.clone();
+        }
+    }
+}
+
+/// Back tint for each source deck, so face-down cards from a multi-deck shoe
+/// can be sorted back into their decks without giving away anything on the
+/// face. Decks past the end of the list reuse its colours.
+/// While disabled every back is drawn untinted.
+#[derive(Resource)]
+struct BackTints {
+    enabled: bool,
+    decks: Vec<Color>,
+}
+
+impl Default for BackTints {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            decks: vec![
+                Color::rgb(0.7, 0.8, 1.0),
+                Color::rgb(1.0, 0.7, 0.75),
+                Color::rgb(0.7, 1.0, 0.7),
+                Color::rgb(1.0, 0.85, 0.6),
+            ],
+        }
+    }
+}
+
+impl BackTints {
+    fn deck(&self, deck: usize) -> Color {
+        if self.decks.is_empty() {
+            return Color::WHITE;
+        }
+        self.decks[deck % self.decks.len()]
+    }
+}
+
+fn toggle_back_tints(keys: Res<Input<KeyCode>>, mut tints: ResMut<BackTints>) {
+    if keys.just_pressed(KeyCode::F6) {
+        tints.enabled = !tints.enabled;
+    }
+}
+
+type TintQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Sprite,
+        Option<&'static CardData>,
+        Option<&'static Face>,
+    ),
+    With<Card>,
+>;
+
+/// Only the colour channels are set, so any alpha other systems give the
+/// sprite is kept. Cards without [`CardData`] are never tinted.
+fn apply_back_tint(tints: Res<BackTints>, mut query: TintQuery) {
+    for (mut sprite, card, face) in &mut query {
+        let color = match card {
+            Some(card) if tints.enabled && face != Some(&Face::Up) => tints.deck(card.deck),
+            _ => Color::WHITE,
+        };
+
+        let alpha = sprite.color.a();
+        if sprite.color.r() != color.r()
+            || sprite.color.g() != color.g()
+            || sprite.color.b() != color.b()
+        {
+            sprite.color = color.with_a(alpha);
+        }
+    }
+}
+
+fn card_label(card: CardData) -> Text2dBundle {
+    let color = if card.suit.is_red() {
+        Color::RED
+    } else {
+        Color::BLACK
+    };
+
+    Text2dBundle {
+        text: Text::from_section(
+            card.to_string(),
+            TextStyle {
+                font_size: 80.0,
+                color,
+                ..default()
+            },
+        ),
+        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+        ..default()
+    }
+}
+
+/// The draw pile, drawn as a single card back while the deck has cards left.
+#[derive(Component)]
+struct Stock;
+
+fn stock_bounds() -> Rect {
+    Rect::from_center_size(STOCK_POS, CARD_IMAGE_SIZE.as_vec2() * CARD_SIZE.truncate())
+}
+
+fn show_stock(deck: Res<Deck>, mut query: Query<(&mut Visibility, With<Stock>)>) {
+    if !deck.is_changed() {
+        return;
+    }
+
+    for (mut visibility, _) in &mut query {
+        *visibility = if deck.is_empty() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+/// How many cards a click on the stock turns over onto the waste: 1, or 3 for
+/// the classic harder game.
+#[derive(Resource)]
+struct StockDeal {
+    per_click: usize,
+}
+
+/// Where cards dealt from the stock land, in the cell beside it.
+fn waste_pos(regions: &GridRegions) -> Vec2 {
+    align_grid(regions, STOCK_POS + vec2(150.0, 0.0))
+}
+
+/// How turning over the stock deals cards and takes them back.
+#[derive(SystemParam)]
+struct StockRules<'w> {
+    deal: Res<'w, StockDeal>,
+    source: Res<'w, DealSource>,
+    facing: Res<'w, DealFacing>,
+    recall_order: Res<'w, RecallOrder>,
+}
+
+/// A plain left-press on the stock while nothing is carried.
+#[derive(SystemParam)]
+struct StockClick<'w, 's> {
+    buttons: Res<'w, Input<MouseButton>>,
+    keys: Res<'w, Input<KeyCode>>,
+    world_cursor: Res<'w, WordCursor>,
+    selected: Query<'w, 's, (), SelectedCard>,
+}
+
+impl StockClick<'_, '_> {
+    fn pressed(&self) -> bool {
+        self.buttons.just_pressed(MouseButton::Left)
+            && !inspect_modifier_pressed(&self.keys)
+            && self.selected.is_empty()
+            && stock_bounds().contains(self.world_cursor.0)
+    }
+}
+
+/// The waste pile beside the stock, and what is in it.
+#[derive(SystemParam)]
+struct Waste<'w, 's> {
+    regions: Res<'w, GridRegions>,
+    index: Res<'w, PileIndex>,
+    cards: Query<'w, 's, &'static CardData>,
+    loose: Query<'w, 's, (Entity, &'static LooseCell)>,
+}
+
+impl Waste<'_, '_> {
+    fn pos(&self) -> Vec2 {
+        waste_pos(&self.regions)
+    }
+
+    /// Bottom of the waste first, which is the order it was dealt in. A waste
+    /// of one may have collapsed out of the pile index.
+    fn cards(&self) -> Vec<(Entity, CardData)> {
+        let pile = Pile::new(self.pos());
+        let collapsed = self
+            .loose
+            .iter()
+            .filter(|(_, cell)| cell.0 == pile)
+            .map(|(entity, _)| entity);
+        collapsed
+            .chain(self.index.get(&pile).into_iter().flatten().copied())
+            .filter_map(|entity| Some((entity, *self.cards.get(entity).ok()?)))
+            .collect()
+    }
+}
+
+/// Clicking the stock deals `StockDeal::per_click` cards onto the waste from the
+/// `DealSource` end of the deck, landing with the `DealFacing` side up; face up,
+/// the top one turns over as it lands. Once the deck is empty, a click turns
+/// the waste, collapsed or not, back over into the stock.
+fn click_stock(
+    click: StockClick,
+    rules: StockRules,
+    waste: Waste,
+    mut spawner: CardSpawner,
+    mut despawn: EventWriter<DespawnCard>,
+    mut commands: Commands,
+) {
+    if !click.pressed() {
+        return;
+    }
+
+    let deck = &mut spawner.deck;
+    if deck.is_empty() {
+        let mut recalled = Vec::new();
+        for (entity, card) in waste.cards() {
+            recalled.push(card);
+            despawn.send(DespawnCard(entity));
+        }
+        deck.recall(recalled, *rules.recall_order);
+        return;
+    }
+
+    let face = match *rules.facing {
+        DealFacing::FaceUp => Face::Up,
+        DealFacing::FaceDown => Face::Down,
+    };
+    let count = rules.deal.per_click.min(deck.len());
+    for slot in 0..count {
+        let Some(card) = rules.source.draw(&mut spawner.deck) else {
+            break;
+        };
+
+        let top = slot + 1 == count;
+        let landing = if top { Face::Down } else { face };
+        let entity = spawn_card_at(&mut commands, &spawner.assets, waste.pos(), card, landing);
+        commands.entity(entity).insert(FanSlot(slot, count));
+        if top && face == Face::Up {
+            request_flip(entity, Face::Down, &mut commands);
+        }
+        spawner.spawned.send(CardSpawned);
+    }
+}
+
+fn waste_bounds(regions: &GridRegions) -> Rect {
+    Rect::from_center_size(
+        waste_pos(regions),
+        CARD_IMAGE_SIZE.as_vec2() * CARD_SIZE.truncate(),
+    )
+}
+
+/// Taking the waste's top card and letting go of it mid swipe, sideways at
+/// `speed_threshold` or faster, tucks it under the rest of the waste instead
+/// of dropping it.
+#[derive(Resource)]
+struct FlickWaste {
+    speed_threshold: f32,
+}
+
+/// A flicked card on its way to the bottom of the waste.
+#[derive(Component)]
+struct Flicked;
+
+fn flick_waste(
+    query: Query<(Entity, &DragVelocity, &DragOrigin), SelectedCard>,
+    input: DragInput,
+    regions: Res<GridRegions>,
+    index: Res<PileIndex>,
+    flick: Res<FlickWaste>,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    if !input.drop_pressed(!query.is_empty()) {
+        return;
+    }
+
+    let waste = waste_pos(&regions);
+    let from_waste = waste_bounds(&regions);
+    // Carried cards have already left the pile.
+    let underneath = index.get(&Pile::new(waste)).map_or(0, Vec::len);
+
+    for (entity, velocity, origin) in &query {
+        let flicked =
+            velocity.x.abs() >= flick.speed_threshold && velocity.x.abs() > velocity.y.abs() * 2.0;
+        if !flicked || !from_waste.contains(origin.0) {
+            continue;
+        }
+
+        // Slides back to the waste either way; only sinks if there's a card to reveal.
+        commands
+            .entity(entity)
+            .insert((Dragging(waste), FanSlot(0, 1)));
+        if underneath == 0 {
+            toasts.send(Toast("No more cards in the waste".to_owned()));
+        } else {
+            commands.entity(entity).insert(Flicked);
+        }
+    }
+}
+
+/// Moves flicked cards from the top of the waste, where they land, to the bottom.
+fn sink_flicked(
+    flicked: Query<(Entity, &Pile), With<Flicked>>,
+    mut index: ResMut<PileIndex>,
+    mut commands: Commands,
+) {
+    for (entity, pile) in &flicked {
+        let Some(cards) = index.0.get_mut(pile) else {
+            continue;
+        };
+        let Some(at) = cards.iter().position(|card| *card == entity) else {
+            continue;
+        };
+
+        let card = cards.remove(at);
+        cards.insert(0, card);
+        commands.entity(entity).remove::<Flicked>();
+    }
+}
+
+#[derive(Component)]
+struct PeekPreview(CardData);
+
+const PEEK_LIFT: f32 = 160.0;
+
+fn peek_deck(
+    keys: Res<Input<KeyCode>>,
+    world_cursor: Res<WordCursor>,
+    deck: Res<Deck>,
+    card_assets: Res<CardAssets>,
+    previews: Query<(Entity, &PeekPreview)>,
+    mut commands: Commands,
+) {
+    let peeking = keys.pressed(KeyCode::Q) && stock_bounds().contains(world_cursor.0);
+    let top = deck.last().copied().filter(|_| peeking);
+
+    for (entity, preview) in &previews {
+        if top != Some(preview.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let Some(card) = top else {
+        return;
+    };
+
+    if previews.iter().any(|(_, preview)| preview.0 == card) {
+        return;
+    }
+
+    commands
+        .spawn((
+            PeekPreview(card),
+            RenderLayers::layer(0),
+            SpriteBundle {
+                texture: card_assets.front.clone(),
+                transform: Transform::from_translation(
+                    (STOCK_POS + vec2(0.0, -PEEK_LIFT)).extend(50.0),
+                )
+                .with_scale(CARD_SIZE),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(card_label(card));
+        });
+}
+
+#[derive(Event)]
+struct Reshuffled;
+
+/// Temporary card back used only by the shuffle animation.
+#[derive(Component)]
+struct ShuffleGhost(usize);
+
+#[derive(Resource, Default)]
+struct ShuffleAnimation(Option<Timer>);
+
+/// Presses during a running shuffle are buffered and start the next one.
+fn reshuffle(
+    animation: Res<ShuffleAnimation>,
+    time: Res<Time>,
+    mut buffer: ResMut<InputBuffer>,
+    mut deck: ResMut<Deck>,
+    mut reshuffled: EventWriter<Reshuffled>,
+) {
+    if animation.0.is_none() && buffer.take(Action::Shuffle, time.elapsed_seconds()) {
+        deck.shuffle(&mut rand::thread_rng());
+        reshuffled.send(Reshuffled);
+    }
+}
+
+fn shuffle_animation(
+    mut reshuffled: EventReader<Reshuffled>,
+    mut animation: ResMut<ShuffleAnimation>,
+    mut ghosts: Query<(Entity, &ShuffleGhost, &mut Transform)>,
+    mut keys: ResMut<Input<KeyCode>>,
+    time: Res<Time>,
+    back: BackTexture,
+    mut commands: Commands,
+) {
+    if reshuffled.read().count() > 0 && animation.0.is_none() {
+        for i in 0..SHUFFLE_GHOSTS {
+            commands.spawn((
+                ShuffleGhost(i),
+                RenderLayers::layer(0),
+                SpriteBundle {
+                    texture: back.get(),
+                    transform: Transform::from_translation(STOCK_POS.extend(0.5))
+                        .with_scale(CARD_SIZE),
+                    ..default()
+                },
+            ));
+        }
+
+        animation.0 = Some(Timer::from_seconds(SHUFFLE_DURATION, TimerMode::Once));
+        return;
+    }
+
+    let Some(timer) = &mut animation.0 else {
+        return;
+    };
+
+    timer.tick(time.delta());
+
+    // Escape skips the animation and is used up, so it doesn't also skip the
+    // tutorial or drop the kept selection.
+    let skipped = keys.clear_just_pressed(KeyCode::Escape);
+    if timer.finished() || skipped {
+        for (entity, _, _) in &ghosts {
+            commands.entity(entity).despawn();
+        }
+        animation.0 = None;
+        return;
+    }
+
+    // Riffle twice: the two halves slide out either side of the stock and merge back.
+    let spread = (timer.percent() * 2.0 * PI).sin().abs() * 60.0;
+    for (_, ghost, mut transform) in &mut ghosts {
+        let side = if ghost.0 % 2 == 0 { -1.0 } else { 1.0 };
+        let lift = ghost.0 as f32 * 2.0;
+
+        // Stay below dragged cards, which sit at z >= 1.
+        transform.translation =
+            (STOCK_POS + vec2(side * spread, lift)).extend(0.5 + ghost.0 as f32 * 0.01);
+    }
+}
+
+/// Card notation file, e.g. "AS KH 3D", loaded with L to replace the deck.
+const DECK_FILE: &str = "deck.txt";
+
+fn load_deck(keys: Res<Input<KeyCode>>, mut deck: ResMut<Deck>, mut toasts: EventWriter<Toast>) {
+    if !keys.just_pressed(KeyCode::L) {
+        return;
+    }
+
+    // Imports are held to as many standard decks as the current deck uses.
+    let deck_count = deck.deck_count();
+    let loaded = std::fs::read_to_string(DECK_FILE)
+        .map_err(|err| err.to_string())
+        .and_then(|notation| Deck::from_notation(&notation).map_err(|err| err.to_string()))
+        .and_then(|loaded| {
+            let loaded = loaded.with_deck_count(deck_count);
+            loaded.validate().map_err(|err| err.to_string())?;
+            Ok(loaded)
+        });
+
+    match loaded {
+        Ok(loaded) => {
+            toasts.send(Toast(format!(
+                "Loaded {} cards from {DECK_FILE}",
+                loaded.len()
+            )));
+            *deck = loaded;
+        }
+        Err(err) => {
+            warn!("could not load {DECK_FILE}: {err}");
+            toasts.send(Toast(format!("Could not load {DECK_FILE}: {err}")));
+        }
+    }
+}
+
+const CUT_DURATION: f32 = 0.5;
+
+#[derive(Event)]
+struct DeckCut;
+
+/// One of the two halves shown swapping places when the deck is cut.
+#[derive(Component)]
+struct CutGhost(bool);
+
+#[derive(Resource, Default)]
+struct CutAnimation(Option<Timer>);
+
+fn cut_deck(
+    animation: Res<CutAnimation>,
+    time: Res<Time>,
+    mut buffer: ResMut<InputBuffer>,
+    mut deck: ResMut<Deck>,
+    mut cut: EventWriter<DeckCut>,
+) {
+    if animation.0.is_some() || deck.len() < 2 || !buffer.take(Action::Cut, time.elapsed_seconds())
+    {
+        return;
+    }
+
+    let at = rand::thread_rng().gen_range(1..deck.len());
+    if let Err(err) = deck.cut(at) {
+        warn!("{err}");
+        return;
+    }
+
+    cut.send(DeckCut);
+}
+
+fn cut_animation(
+    mut cut: EventReader<DeckCut>,
+    mut animation: ResMut<CutAnimation>,
+    mut ghosts: Query<(Entity, &CutGhost, &mut Transform)>,
+    time: Res<Time>,
+    back: BackTexture,
+    mut commands: Commands,
+) {
+    if cut.read().count() > 0 && animation.0.is_none() {
+        for top in [false, true] {
+            commands.spawn((
+                CutGhost(top),
+                RenderLayers::layer(0),
+                SpriteBundle {
+                    texture: back.get(),
+                    transform: Transform::from_translation(STOCK_POS.extend(0.5))
+                        .with_scale(CARD_SIZE),
+                    ..default()
+                },
+            ));
+        }
+
+        animation.0 = Some(Timer::from_seconds(CUT_DURATION, TimerMode::Once));
+        return;
+    }
+
+    let Some(timer) = &mut animation.0 else {
+        return;
+    };
+
+    timer.tick(time.delta());
+
+    if timer.finished() {
+        for (entity, _, _) in &ghosts {
+            commands.entity(entity).despawn();
+        }
+        animation.0 = None;
+        return;
+    }
+
+    // The halves slide apart, pass each other in depth, then slide back together.
+    let t = timer.percent();
+    let spread = (t * PI).sin() * 70.0;
+    for (_, ghost, mut transform) in &mut ghosts {
+        let (side, z) = if ghost.0 {
+            (1.0, 0.6 - 0.2 * t)
+        } else {
+            (-1.0, 0.4 + 0.2 * t)
+        };
+        transform.translation = (STOCK_POS + vec2(side * spread, 0.0)).extend(z);
+    }
+}
+
+/// How released cards settle onto their target.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+enum SettleMode {
+    #[default]
+    Lerp,
+    Spring,
+}
+
+/// Cards released within this distance of an existing pile land on it rather
+/// than in the cell under the cursor.
+#[derive(Resource)]
+struct SnapRadius(f32);
+
+/// The position of the pile nearest `point`, if one is within `radius`.
+fn nearest_pile(index: &PileIndex, point: Vec2, radius: f32) -> Option<Vec2> {
+    index
+        .keys()
+        .map(|pile| vec2(pile.0 as f32, pile.1 as f32))
+        .filter(|pile| pile.distance(point) <= radius)
+        .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)))
+}
+
+/// The curve `SettleMode::Lerp` settles cards along.
+#[derive(Resource, Default)]
+struct SettleEase(EaseCurve);
+
+/// Seconds a card takes to settle in `SettleMode::Lerp`.
+const SETTLE_DURATION: f32 = 0.25;
+
+/// How far a card is through settling from `from` to `to`.
+#[derive(Component)]
+struct SettleProgress {
+    from: Vec2,
+    to: Vec2,
+    t: f32,
+}
+
+fn cycle_settle_ease(
+    keys: Res<Input<KeyCode>>,
+    mut ease: ResMut<SettleEase>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keys.just_pressed(KeyCode::E) {
+        ease.0 = ease.0.next();
+        toasts.send(Toast(format!("Settling with {}", ease.0)));
+    }
+}
+
+/// Spring-damper constants for `SettleMode::Spring`. Under-damped for a slight overshoot.
+#[derive(Resource)]
+struct SpringSettle {
+    stiffness: f32,
+    damping: f32,
+}
+
+#[derive(Component, Default, Deref)]
+struct SettleVelocity(Vec2);
+
+/// Distance (and speed) below which a spring-settled card snaps to its target.
+const SPRING_SETTLE_TOLERANCE: f32 = 0.5;
+/// Largest step the spring integrates at once, so frame hitches can't blow it up.
+const MAX_SPRING_STEP: f32 = 1.0 / 30.0;
+
+fn toggle_settle_mode(keys: Res<Input<KeyCode>>, mut settle_mode: ResMut<SettleMode>) {
+    if keys.just_pressed(KeyCode::F3) {
+        *settle_mode = match *settle_mode {
+            SettleMode::Lerp => SettleMode::Spring,
+            SettleMode::Spring => SettleMode::Lerp,
+        };
+    }
+}
+
+/// The piles a released card can land in, and how far it reaches for them.
+#[derive(SystemParam)]
+struct DropTargets<'w, 's> {
+    piled: Query<'w, 's, &'static CardData, With<Pile>>,
+    index: Res<'w, PileIndex>,
+    foundations: Res<'w, Foundations>,
+    board: Res<'w, BoardBounds>,
+    extent: Res<'w, MaxPileExtent>,
+    snap_radius: Res<'w, SnapRadius>,
+}
+
+/// How a released card moves the rest of the way to its target.
+#[derive(SystemParam)]
+struct Settling<'w> {
+    mode: Res<'w, SettleMode>,
+    spring: Res<'w, SpringSettle>,
+    ease: Res<'w, SettleEase>,
+    time: Res<'w, Time>,
+}
+
+type SettlingQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Dragging,
+        &'static mut Transform,
+        Option<&'static FanSlot>,
+        Option<&'static mut SettleVelocity>,
+        Option<&'static mut SettleProgress>,
+        Option<&'static CardData>,
+        Option<&'static DragOrigin>,
+        UnselectedCard,
+    ),
+>;
+
+fn finish_drag_selected(
+    mut query: SettlingQuery,
+    targets: DropTargets,
+    settling: Settling,
+    mut released: RemovedComponents<Selected>,
+    mut dropped: EventWriter<CardDropped>,
+    mut toasts: EventWriter<Toast>,
+    mut commands: Commands,
+) {
+    let DropTargets {
+        piled,
+        index,
+        foundations,
+        board,
+        extent,
+        snap_radius,
+    } = &targets;
+    let Settling {
+        mode: settle_mode,
+        spring,
+        ease,
+        time,
+    } = &settling;
+    let released = released.read().collect::<HashSet<_>>();
+    // Runs before `align_placed`, so a fresh drop is still at the release point.
+    for (entity, mut dragging, _, _, _, _, _, origin, _) in &mut query {
+        // Cards that were never picked up are left to `recover_offboard`.
+        if !board.contains(dragging.0) && origin.is_some_and(|origin| board.contains(origin.0)) {
+            reject_drop(
+                entity,
+                origin,
+                "Cards can't be dropped off the board".to_owned(),
+                &mut toasts,
+                &mut commands,
+            );
+            continue;
+        }
+
+        // Only on release, so a card settling into one cell isn't pulled on
+        // into another pile within reach of it.
+        if !released.contains(&entity) {
+            continue;
+        }
+        if let Some(pile) = nearest_pile(index, dragging.0, snap_radius.0) {
+            if dragging.0 != pile {
+                dragging.0 = pile;
+            }
+        }
+    }
+
+    // Everything settling onto each foundation, so cards of one drop are
+    // judged together whichever lands first.
+    let mut inbound = HashMap::<Pile, Vec<CardData>>::default();
+    for (_, dragging, _, _, _, _, card, ..) in &query {
+        if let Some(card) = card.filter(|_| foundations.contains(dragging.0)) {
+            inbound
+                .entry(Pile::new(dragging.0))
+                .or_default()
+                .push(*card);
+        }
+    }
+
+    for (entity, dragging, mut transform, slot, velocity, progress, card, origin, _) in &mut query {
+        let on_foundation = foundations.contains(dragging.0);
+
+        // Foundations stack squarely rather than in the fan they were carried in.
+        let target = match slot {
+            Some(slot) if !on_foundation => dragging.0 + pile_offset_for(slot.0, slot.1, extent.0),
+            _ => dragging.0,
+        };
+
+        if transform.translation.xy().floor() == target.floor() {
+            let pile = Pile::new(dragging.0);
+
+            if on_foundation && !foundation_accepts(pile, card, index, piled, &inbound) {
+                let reason = match card {
+                    Some(card) => format!("{card} doesn't fit this foundation"),
+                    None => "Only playing cards go on foundations".to_owned(),
+                };
+                reject_drop(entity, origin, reason, &mut toasts, &mut commands);
+                continue;
+            }
+
+            commands
+                .entity(entity)
+                .remove::<(Dragging, SettleVelocity, SettleProgress)>();
+            commands.entity(entity).insert(pile);
+            dropped.send(CardDropped(entity));
+        }
+
+        match (**settle_mode, velocity) {
+            (SettleMode::Spring, Some(mut velocity)) => {
+                let dt = time.delta_seconds().min(MAX_SPRING_STEP);
+                let pos = transform.translation.truncate();
+
+                let current = velocity.0;
+                velocity.0 += ((target - pos) * spring.stiffness - current * spring.damping) * dt;
+                let mut next = pos + velocity.0 * dt;
+
+                if next.distance(target) < SPRING_SETTLE_TOLERANCE
+                    && velocity.length() < SPRING_SETTLE_TOLERANCE * 10.0
+                {
+                    next = target;
+                    velocity.0 = Vec2::ZERO;
+                }
+
+                transform.translation = next.extend(transform.translation.z * 0.85);
+            }
+            (SettleMode::Spring, None) => {
+                commands.entity(entity).insert(SettleVelocity::default());
+            }
+            (SettleMode::Lerp, _) => {
+                let pos = transform.translation.truncate();
+                let z = transform.translation.z * 0.85;
+
+                match progress {
+                    // A new target (say, sent back from a foundation) starts over.
+                    Some(mut progress) if progress.to == target => {
+                        progress.t = (progress.t + time.delta_seconds() / SETTLE_DURATION).min(1.0);
+                        let eased = progress.from.lerp(target, ease.0.apply(progress.t));
+                        transform.translation = eased.extend(z);
+                    }
+                    _ => {
+                        commands.entity(entity).insert(SettleProgress {
+                            from: pos,
+                            to: target,
+                            t: 0.0,
+                        });
+                    }
+                }
+            }
+        }
+
+        transform.scale = transform.scale.lerp(CARD_SIZE, 0.15);
+    }
+}
+
+type ShrinkingQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        With<Card>,
+        Without<Selected>,
+        Without<LandBounce>,
+        Without<Completing>,
+    ),
+>;
+
+fn non_selected(mut query: ShrinkingQuery) {
+    for (mut transform, _, _, _, _) in &mut query {
+        transform.scale = transform.scale.lerp(CARD_SIZE, 0.2);
+    }
+}
+
+/// Every refused drop ends here: the card says why, then wiggles its way back
+/// to where it was picked up. A card that was never picked up has nowhere to
+/// go back to, so it wiggles where it is and stops there.
+fn reject_drop(
+    entity: Entity,
+    origin: Option<&DragOrigin>,
+    reason: String,
+    toasts: &mut EventWriter<Toast>,
+    commands: &mut Commands,
+) {
+    toasts.send(Toast(reason));
+
+    let mut card = commands.entity(entity);
+    card.insert(Wiggle(Timer::from_seconds(
+        WIGGLE_DURATION,
+        TimerMode::Once,
+    )));
+    match origin {
+        Some(origin) => card.insert(Dragging(origin.0)),
+        None => card.remove::<(Dragging, SettleVelocity, SettleProgress)>(),
+    };
+}
+
+const WIGGLE_DURATION: f32 = 0.4;
+/// Peak tilt either way, in radians.
+const WIGGLE_ANGLE: f32 = 0.15;
+const WIGGLE_SHAKES: f32 = 3.0;
+
+/// A head-shake of rotation played on a card whose drop was refused.
+#[derive(Component, Deref, DerefMut)]
+struct Wiggle(Timer);
+
+fn wiggle(
+    mut query: Query<(Entity, &mut Transform, &mut Wiggle)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut wiggle) in &mut query {
+        wiggle.tick(time.delta());
+
+        if wiggle.finished() {
+            transform.rotation = Quat::IDENTITY;
+            commands.entity(entity).remove::<Wiggle>();
+            continue;
+        }
+
+        let t = wiggle.percent();
+        let angle = WIGGLE_ANGLE * (t * WIGGLE_SHAKES * 2.0 * PI).sin() * (1.0 - t);
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
+
+/// Squash-and-stretch played once when a card lands in a pile.
+#[derive(Component, Deref, DerefMut)]
+struct LandBounce(Timer);
+
+#[derive(Resource)]
+struct LandBounceConfig {
+    /// Peak fraction the scale stretches by.
+    magnitude: f32,
+    duration: f32,
+}
+
+fn start_land_bounce(
+    mut dropped: EventReader<CardDropped>,
+    config: Res<LandBounceConfig>,
+    mut commands: Commands,
 ) {
-    let (camera, camera_transform, _) = camera_query.single();
+    for CardDropped(entity) in dropped.read() {
+        if let Some(mut card) = commands.get_entity(*entity) {
+            card.insert(LandBounce(Timer::from_seconds(
+                config.duration,
+                TimerMode::Once,
+            )));
+        }
+    }
+}
 
-    let Some(cursor_position) = windows.single().cursor_position() else {
-        return;
-    };
+fn land_bounce(
+    mut query: Query<(Entity, &mut Transform, &mut LandBounce)>,
+    config: Res<LandBounceConfig>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut bounce) in &mut query {
+        bounce.tick(time.delta());
 
-    // Calculate a world position based on the cursor's position.
-    let Some(point) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
-        return;
-    };
+        if bounce.finished() {
+            transform.scale = CARD_SIZE;
+            commands.entity(entity).remove::<LandBounce>();
+            continue;
+        }
 
-    world_cursor.0 = point;
+        // A decaying wobble: wide and flat first, then tall and thin, then at rest.
+        let t = bounce.percent();
+        let stretch = config.magnitude * (t * 2.0 * PI).sin() * (1.0 - t);
+        transform.scale = CARD_SIZE * Vec3::new(1.0 + stretch, 1.0 - stretch, 1.0);
+    }
+}
 
-    gizmos.circle_2d(point, 10., Color::WHITE);
+/// Which side of a card is showing. Only cards with a `CardData` face have one.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    Up,
+    Down,
 }
 
-const CARD_SIZE: Vec3 = Vec3::new(0.5, 0.5, 1.0);
+impl Face {
+    fn flipped(self) -> Self {
+        match self {
+            Face::Up => Face::Down,
+            Face::Down => Face::Up,
+        }
+    }
+}
 
-fn align_grid(bounds: &Bounds, offset: Vec2) -> Vec2 {
-    ((bounds.center() * 1.0 / bounds.0.size()).floor() * bounds.0.size())
-        + bounds.half_size()
-        + offset
+/// A flip in progress. `t` runs from 0 to 1 and the card is edge-on at 0.5,
+/// which is exactly when its texture changes over to `to`.
+#[derive(Component)]
+struct FlipAnim {
+    t: f32,
+    to: Face,
 }
 
-#[derive(Default)]
-struct SpawnCounter(usize);
+const FLIP_DURATION: f32 = 0.3;
 
-fn create_card(
-    world_cursor: Res<WordCursor>,
+/// Which inputs flip a card. Both can be active at once.
+#[derive(Resource)]
+struct FlipInput {
+    double_click: bool,
+    key: bool,
+}
+
+/// Starts turning `entity` over to the other side from `face`. Every way of
+/// flipping a card goes through here.
+fn request_flip(entity: Entity, face: Face, commands: &mut Commands) {
+    commands.entity(entity).insert(FlipAnim {
+        t: 0.0,
+        to: face.flipped(),
+    });
+}
+
+/// Whether picking up a face-down card turns it face up.
+#[derive(Resource)]
+struct RevealOnSelect(bool);
+
+/// Goes by `CardPickedUp`, which is sent once per pickup, so holding a card
+/// never flips it again.
+fn reveal_on_select(
+    reveal: Res<RevealOnSelect>,
+    cards: Query<&Face, Without<FlipAnim>>,
+    mut picked_up: EventReader<CardPickedUp>,
+    mut commands: Commands,
+) {
+    for CardPickedUp(entity) in picked_up.read() {
+        if !reveal.0 {
+            continue;
+        }
+
+        if let Ok(Face::Down) = cards.get(*entity) {
+            request_flip(*entity, Face::Down, &mut commands);
+        }
+    }
+}
+
+type FlipTargetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Bounds, &'static Transform, &'static Face),
+    (Interactive, Without<FlipAnim>),
+>;
+
+/// Double-clicking a card turns it over.
+fn flip_card(
     buttons: Res<Input<MouseButton>>,
-    asset_server: Res<AssetServer>,
-    mut counter: Local<SpawnCounter>,
-    commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    flip_input: Res<FlipInput>,
+    world_cursor: Res<WordCursor>,
+    query: FlipTargetQuery,
+    mut clicks: Clicks,
+    mut commands: Commands,
 ) {
-    if buttons.just_pressed(MouseButton::Right) {
-        let colors = [
-            "card_back_blue.png",
-            "card_back_purple.png",
-            "card_back_red.png",
-        ];
+    if !flip_input.double_click
+        || !buttons.just_pressed(MouseButton::Left)
+        || inspect_modifier_pressed(&keys)
+    {
+        return;
+    }
 
-        counter.0 += 1;
-        counter.0 %= colors.len();
+    let Some((entity, face)) = query
+        .iter()
+        .filter(|(_, bounds, transform, _)| hit_test(bounds.0, transform.rotation, world_cursor.0))
+        .max_by(|(_, _, a, _), (_, _, b, _)| a.translation.z.total_cmp(&b.translation.z))
+        .map(|(entity, _, _, face)| (entity, *face))
+    else {
+        return;
+    };
 
-        spawn_card(world_cursor.0, colors[counter.0], commands, asset_server);
+    if clicks.register_click(entity) {
+        request_flip(entity, face, &mut commands);
     }
 }
 
-type SelectedCard = (With<Card>, With<Selected>);
-type UnselectedCard = (With<Card>, Without<Selected>);
+type HoveredFaceQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static Face), (With<Hovered>, Without<FlipAnim>)>;
 
-fn select_card(
-    query: Query<(Entity, &Bounds, With<Card>)>,
-    world_cursor: Res<WordCursor>,
-    buttons: Res<Input<MouseButton>>,
+/// F turns over the hovered card. A press while the hovered card is still
+/// turning over waits in the `InputBuffer` and flips it back once it lands;
+/// with nothing hovered, or a card without a face, the press is used up doing
+/// nothing.
+fn flip_hovered(
+    flip_input: Res<FlipInput>,
+    hovered: HoveredFaceQuery,
+    turning: Query<(), (With<Hovered>, With<FlipAnim>)>,
+    time: Res<Time>,
+    mut buffer: ResMut<InputBuffer>,
     mut commands: Commands,
 ) {
-    for (entity, bounds, _) in &query {
-        if buttons.just_pressed(MouseButton::Left) && bounds.0.contains(world_cursor.0) {
-            commands.entity(entity).insert(Selected);
-            commands.entity(entity).remove::<Pile>();
-        } else if buttons.just_released(MouseButton::Left) {
-            commands.entity(entity).remove::<Selected>();
-        }
+    if !flip_input.key || (hovered.is_empty() && !turning.is_empty()) {
+        return;
+    }
+    if !buffer.take(Action::Flip, time.elapsed_seconds()) {
+        return;
+    }
+
+    for (entity, face) in &hovered {
+        request_flip(entity, *face, &mut commands);
     }
 }
 
-fn drag_selected(
-    mut query: Query<(Entity, &mut Transform, &Bounds, SelectedCard)>,
-    world_cursor: Res<WordCursor>,
+type FlipQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static mut Handle<Image>,
+        &'static mut Face,
+        &'static mut FlipAnim,
+        Option<&'static Children>,
+    ),
+>;
+
+/// Narrows the card to nothing and back out, swapping faces while it is edge-on.
+fn flip_animation(
+    mut query: FlipQuery,
+    mut labels: Query<&mut Visibility, (With<Text>, Without<PileBadge>)>,
+    card_assets: Res<CardAssets>,
+    theme: Res<BackTheme>,
+    time: Res<Time>,
     mut commands: Commands,
-    mut gizmos: Gizmos,
 ) {
-    for (i, (entity, mut transform, bounds, _)) in query.iter_mut().enumerate() {
-        let index = (i as f32) + 1.0;
-        let offset = (i as f32) * 10.0;
+    for (entity, mut transform, mut texture, mut face, mut flip, children) in &mut query {
+        flip.t = (flip.t + time.delta_seconds() / FLIP_DURATION).min(1.0);
 
-        let dragging = Dragging(world_cursor.0);
+        if flip.t >= 0.5 && *face != flip.to {
+            *face = flip.to;
+            *texture = match flip.to {
+                Face::Up => card_assets.front.clone(),
+                Face::Down => card_assets.back(*theme),
+            };
 
-        if i == 0 {
-            let target_bounds = Bounds(Rect::from_center_size(world_cursor.0, bounds.size()));
-            let grid_pos = align_grid(&target_bounds, Vec2::ZERO);
-            gizmos.rect_2d(grid_pos, 0.0, target_bounds.size(), Color::WHITE);
+            for child in children.iter().flat_map(|children| children.iter()) {
+                if let Ok(mut visibility) = labels.get_mut(*child) {
+                    *visibility = match flip.to {
+                        Face::Up => Visibility::Inherited,
+                        Face::Down => Visibility::Hidden,
+                    };
+                }
+            }
         }
 
-        transform.translation = transform.translation.lerp(
-            Vec3::new(offset + world_cursor.0.x, offset + world_cursor.0.y, index),
-            0.1 * index,
-        );
+        // Width follows the height so any scale the card has otherwise is kept.
+        transform.scale.x = transform.scale.y * (1.0 - 2.0 * flip.t).abs();
 
-        transform.scale = transform.scale.lerp(CARD_SIZE * 1.2, 0.1);
-        commands.entity(entity).insert(dragging);
+        if flip.t >= 1.0 {
+            transform.scale.x = transform.scale.y;
+            commands.entity(entity).remove::<FlipAnim>();
+        }
     }
 }
 
-fn show_piles(query: Query<(&Pile, &Bounds)>, mut gizmos: Gizmos) {
-    let mut pile_counts = HashMap::new();
-    for (pile, _bounds) in &query {
-        pile_counts.entry(pile).and_modify(|c| *c += 1).or_insert(1);
+/// A faded copy of a face-down card's front, shown while `reveal_all` is held.
+#[derive(Component)]
+struct RevealOverlay;
+
+/// While F11 is held, every face-down card shows its front at reduced opacity.
+/// Purely visual: the cards' `Face` is left alone.
+fn reveal_all(
+    keys: Res<Input<KeyCode>>,
+    cards: Query<(Entity, &CardData, &Face)>,
+    overlays: Query<(Entity, &Parent), With<RevealOverlay>>,
+    card_assets: Res<CardAssets>,
+    mut commands: Commands,
+) {
+    let revealing = keys.pressed(KeyCode::F11);
+    let mut revealed = HashSet::new();
+
+    for (overlay, parent) in &overlays {
+        let face_down = cards
+            .get(parent.get())
+            .is_ok_and(|(_, _, face)| *face == Face::Down);
+
+        if revealing && face_down {
+            revealed.insert(parent.get());
+        } else {
+            commands.entity(overlay).despawn_recursive();
+        }
     }
 
-    for (pile, bounds) in &query {
-        let count = pile_counts[pile];
-        if count > 1 {
-            gizmos.circle_2d(pile.pos() + bounds.half_size(), 20.0, Color::ORANGE_RED);
+    if !revealing {
+        return;
+    }
+
+    for (entity, card, face) in &cards {
+        if *face != Face::Down || revealed.contains(&entity) {
+            continue;
         }
+
+        let overlay = commands
+            .spawn((
+                RevealOverlay,
+                SpriteBundle {
+                    texture: card_assets.front.clone(),
+                    sprite: Sprite {
+                        color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.05),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                let mut label = card_label(*card);
+                label.text.sections[0].style.color.set_a(0.5);
+                parent.spawn(label);
+            })
+            .id();
+        commands.entity(entity).add_child(overlay);
     }
 }
 
-fn finish_drag_selected(
-    mut query: Query<(Entity, &Dragging, &mut Transform, UnselectedCard)>,
-    mut commands: Commands,
-) {
-    for (entity, dragging, mut transform, _) in &mut query {
-        if transform.translation.xy().floor() == dragging.0.floor() {
-            println!("finished dragging: {:?}", entity);
-            commands.entity(entity).remove::<Dragging>();
-            commands.entity(entity).insert(Pile::new(dragging.0));
-        }
+/// The playable area. Cards that end up entirely outside it are brought back.
+#[derive(Resource, Deref)]
+struct BoardBounds(Rect);
 
-        transform.translation = transform
-            .translation
-            .lerp(Vec3::new(dragging.0.x, dragging.0.y, 0.0), 0.15);
+/// Whether `recover_offboard` is active.
+#[derive(Resource)]
+struct RecoverOffboard(bool);
 
-        transform.scale = transform.scale.lerp(CARD_SIZE, 0.15);
+fn toggle_recover_offboard(keys: Res<Input<KeyCode>>, mut recover: ResMut<RecoverOffboard>) {
+    if keys.just_pressed(KeyCode::F5) {
+        recover.0 = !recover.0;
     }
 }
 
-fn non_selected(mut query: Query<(&mut Transform, With<Card>, Without<Selected>)>) {
-    for (mut transform, _, _) in &mut query {
-        transform.scale = transform.scale.lerp(CARD_SIZE, 0.2);
+/// Centre of the grid cell nearest to `point` that lies inside `board`.
+fn nearest_cell_inside(regions: &GridRegions, board: Rect, point: Vec2) -> Vec2 {
+    let mut cell = align_grid(regions, point.clamp(board.min, board.max));
+    let step = regions.config_at(cell).cell_size;
+
+    // Snapping can land on a cell centred just past the edge; step back inwards.
+    if cell.x < board.min.x {
+        cell.x += step.x;
+    } else if cell.x > board.max.x {
+        cell.x -= step.x;
     }
+    if cell.y < board.min.y {
+        cell.y += step.y;
+    } else if cell.y > board.max.y {
+        cell.y -= step.y;
+    }
+
+    cell
 }
 
-fn align_placed(mut query: Query<(&Bounds, &mut Dragging, UnselectedCard)>) {
-    for (bounds, mut dragging, _) in &mut query {
-        dragging.0 = align_grid(
-            &Bounds(Rect::from_center_size(dragging.0, bounds.size() * 1.2)),
-            Vec2::ZERO,
-        );
+type OffboardQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Bounds, Option<&'static Dragging>),
+    (UnselectedCard, Without<Disappearing>),
+>;
+
+/// Safety net for cards lost off the board (a hard fling, say): any card fully
+/// outside `BoardBounds` is sent back to the nearest cell on the board.
+fn recover_offboard(
+    query: OffboardQuery,
+    board: Res<BoardBounds>,
+    recover: Res<RecoverOffboard>,
+    regions: Res<GridRegions>,
+    mut commands: Commands,
+) {
+    if !recover.0 {
+        return;
+    }
+
+    for (entity, bounds, dragging) in &query {
+        if !bounds.0.intersect(board.0).is_empty() {
+            continue;
+        }
+
+        // Already heading back.
+        if dragging.is_some_and(|dragging| board.contains(dragging.0)) {
+            continue;
+        }
+
+        let target = nearest_cell_inside(&regions, board.0, bounds.center());
+        commands
+            .entity(entity)
+            .remove::<(Pile, LooseCell, FanSlot)>()
+            .insert(Dragging(target));
+    }
+}
+
+fn align_placed(mut query: Query<(&mut Dragging, UnselectedCard)>, regions: Res<GridRegions>) {
+    for (mut dragging, _) in &mut query {
+        dragging.0 = align_grid(&regions, dragging.0);
     }
 }
 
@@ -186,17 +5682,47 @@ struct WordCursor(Vec2);
 #[derive(Component)]
 struct Card;
 
-#[derive(Component, PartialEq, Eq, Hash)]
+/// Free-form labels that rules and scoring can branch on, e.g. "wild" or "bonus".
+/// Each is a single word, so a save can list them space-separated.
+#[derive(Component, Default, Clone, Deref)]
+pub struct Tags(HashSet<String>);
+
+impl Tags {
+    /// Whether `tag` was new. Empty tags and ones with whitespace are refused.
+    pub fn add(&mut self, tag: impl Into<String>) -> Result<bool, String> {
+        let tag = tag.into();
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            return Err(format!("tag {tag:?} isn't a single word"));
+        }
+        Ok(self.0.insert(tag))
+    }
+
+    pub fn remove(&mut self, tag: &str) -> bool {
+        self.0.remove(tag)
+    }
+
+    pub fn has(&self, tag: &str) -> bool {
+        self.0.contains(tag)
+    }
+}
+
+/// Every card in `world` that carries `tag`.
+pub fn cards_with_tag(world: &World, tag: &str) -> Vec<Entity> {
+    world
+        .iter_entities()
+        .filter(|entity| entity.contains::<Card>())
+        .filter(|entity| entity.get::<Tags>().is_some_and(|tags| tags.has(tag)))
+        .map(|entity| entity.id())
+        .collect()
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct Pile(i32, i32);
 
 impl Pile {
     fn new(pos: Vec2) -> Self {
         Self(pos.x as i32, pos.y as i32)
     }
-
-    fn pos(&self) -> Vec2 {
-        vec2(self.0 as f32, self.1 as f32)
-    }
 }
 
 #[derive(Component, Deref)]
@@ -205,9 +5731,19 @@ struct Dragging(Vec2);
 #[derive(Component)]
 struct Selected;
 
+/// A card's extent ignoring its rotation: centre from the translation, size
+/// from the texture and scale. Hit tests should go through `hit_test`.
 #[derive(Component, Deref)]
 struct Bounds(Rect);
 
+/// Whether `point` lies on a card with unrotated extent `rect`, turned by
+/// `rotation` about its centre.
+fn hit_test(rect: Rect, rotation: Quat, point: Vec2) -> bool {
+    let local = rotation.inverse() * (point - rect.center()).extend(0.0);
+    let half = rect.half_size();
+    local.x.abs() <= half.x && local.y.abs() <= half.y
+}
+
 fn update_bounds(
     mut query: Query<(&Transform, &Handle<Image>, &mut Bounds, With<Sprite>)>,
     assets: Res<Assets<Image>>,
@@ -225,18 +5761,129 @@ fn update_bounds(
     }
 }
 
-fn spawn_card(pos: Vec2, card: &str, mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        Card,
-        Dragging(pos),
-        RenderLayers::layer(0),
-        Bounds(Rect::new(0.0, 0.0, 100.0, 100.0)),
-        SpriteBundle {
-            texture: asset_server.load(card.to_string()),
-            transform: Transform::from_xyz(0., 0., 0.).with_scale(CARD_SIZE),
-            ..default()
-        },
-    ));
+fn spawn_card(pos: Vec2, from: Vec2, texture: Handle<Image>, commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            Card,
+            Tags::default(),
+            MotionTrail::default(),
+            Dragging(pos),
+            Bounds(Rect::new(0.0, 0.0, 100.0, 100.0)),
+            SpriteBundle {
+                texture,
+                transform: Transform::from_translation(from.extend(0.0)).with_scale(CARD_SIZE),
+                ..default()
+            },
+        ))
+        .id()
+}
+
+/// The render layer cards can be picked up, hovered and flipped on.
+const INTERACTIVE_LAYER: u8 = 0;
+/// Also drawn by the cards camera, but nothing on it can be interacted with.
+const BACKGROUND_LAYER: u8 = 2;
+
+/// The render layer newly spawned cards go on.
+#[derive(Resource)]
+struct CardLayer(u8);
+
+/// A card off the `INTERACTIVE_LAYER`: drawn like any other, but ignored by
+/// hit-testing so it can't be hovered, picked up or flipped.
+#[derive(Component)]
+struct Inert;
+
+fn apply_card_layer(
+    layer: Res<CardLayer>,
+    added: Query<Entity, Added<Card>>,
+    mut commands: Commands,
+) {
+    for entity in &added {
+        let mut card = commands.entity(entity);
+        card.insert(RenderLayers::layer(layer.0));
+        if layer.0 != INTERACTIVE_LAYER {
+            card.insert(Inert);
+        }
+    }
+}
+
+/// Y switches new cards between the interactive and background layers.
+fn toggle_card_layer(
+    keys: Res<Input<KeyCode>>,
+    mut layer: ResMut<CardLayer>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keys.just_pressed(KeyCode::Y) {
+        return;
+    }
+
+    if layer.0 == INTERACTIVE_LAYER {
+        layer.0 = BACKGROUND_LAYER;
+        toasts.send(Toast("New cards go in the inert background".to_owned()));
+    } else {
+        layer.0 = INTERACTIVE_LAYER;
+        toasts.send(Toast("New cards are interactive".to_owned()));
+    }
+}
+
+/// All card removals go through this event so that child entities (badges, labels)
+/// are despawned along with their card instead of being orphaned, and nothing
+/// that remembers cards by entity is left pointing at it.
+#[derive(Event)]
+struct DespawnCard(Entity);
+
+/// Everything that keeps track of cards by entity, and so has to let go of
+/// the ones despawned.
+#[derive(SystemParam)]
+struct CardRecords<'w> {
+    index: ResMut<'w, PileIndex>,
+    inspected: ResMut<'w, Inspected>,
+    clicks: ResMut<'w, ClickTracker>,
+    focus: ResMut<'w, CameraFocus>,
+    ids: ResMut<'w, CardIds>,
+}
+
+impl CardRecords<'_> {
+    fn forget(&mut self, despawned: &HashSet<Entity>) {
+        for &entity in despawned {
+            self.ids.forget(entity);
+        }
+        for pile in self.index.0.values_mut() {
+            pile.retain(|card| !despawned.contains(card));
+        }
+        self.index.0.retain(|_, pile| !pile.is_empty());
+        if self
+            .inspected
+            .0
+            .is_some_and(|entity| despawned.contains(&entity))
+        {
+            self.inspected.0 = None;
+        }
+        self.clicks
+            .0
+            .retain(|entity, _| !despawned.contains(entity));
+        if matches!(self.focus.target, FocusTarget::Entity(entity) if despawned.contains(&entity)) {
+            self.focus.target = FocusTarget::Free;
+            self.focus.blend = None;
+        }
+    }
+}
+
+fn despawn_cards(
+    mut events: EventReader<DespawnCard>,
+    cards: Query<Entity, With<Card>>,
+    mut records: CardRecords,
+    mut commands: Commands,
+) {
+    let mut despawned = HashSet::new();
+    for DespawnCard(entity) in events.read() {
+        if cards.contains(*entity) && despawned.insert(*entity) {
+            commands.entity(*entity).despawn_recursive();
+        }
+    }
+
+    if !despawned.is_empty() {
+        records.forget(&despawned);
+    }
 }
 
 #[derive(Component)]
@@ -248,8 +5895,64 @@ struct AnimationIndices {
 #[derive(Component, Deref, DerefMut)]
 struct AnimationTimer(Timer);
 
+/// Multiplier applied to sprite animation playback; 0 pauses all animations.
+#[derive(Resource, Deref)]
+struct AnimationSpeed(f32);
+
+const ANIMATION_SPEED_STEP: f32 = 0.25;
+const MAX_ANIMATION_SPEED: f32 = 4.0;
+
+fn adjust_animation_speed(keys: Res<Input<KeyCode>>, mut speed: ResMut<AnimationSpeed>) {
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        speed.0 = (speed.0 - ANIMATION_SPEED_STEP).max(0.0);
+    }
+
+    if keys.just_pressed(KeyCode::BracketRight) {
+        speed.0 = (speed.0 + ANIMATION_SPEED_STEP).min(MAX_ANIMATION_SPEED);
+    }
+}
+
+#[derive(Resource)]
+struct Paused(bool);
+
+/// Whether cards can still be picked up and dragged while paused.
+#[derive(Resource)]
+struct DragWhilePaused(bool);
+
+#[derive(Component)]
+struct PauseBanner;
+
+fn paused(paused: Res<Paused>) -> bool {
+    paused.0
+}
+
+fn dragging_allowed(paused: Res<Paused>, drag_while_paused: Res<DragWhilePaused>) -> bool {
+    !paused.0 || drag_while_paused.0
+}
+
+fn toggle_pause(keys: Res<Input<KeyCode>>, mut paused: ResMut<Paused>) {
+    if keys.just_pressed(KeyCode::P) {
+        paused.0 = !paused.0;
+    }
+}
+
+fn show_pause_banner(paused: Res<Paused>, mut query: Query<(&mut Visibility, With<PauseBanner>)>) {
+    if !paused.is_changed() {
+        return;
+    }
+
+    for (mut visibility, _) in &mut query {
+        *visibility = if paused.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
 fn animate_sprite(
     time: Res<Time>,
+    speed: Res<AnimationSpeed>,
     mut query: Query<(
         &AnimationIndices,
         &mut AnimationTimer,
@@ -257,23 +5960,53 @@ fn animate_sprite(
     )>,
 ) {
     for (indices, mut timer, mut sprite) in &mut query {
-        timer.tick(time.delta());
-        if timer.just_finished() {
-            sprite.index = if sprite.index >= indices.last {
-                indices.first
-            } else {
-                sprite.index + 1
-            };
-        }
+        timer.tick(time.delta().mul_f32(speed.max(0.0)));
+        // Sped up, one frame can cover several animation steps.
+        let steps = timer.times_finished_this_tick() as usize;
+        let frames = indices.last - indices.first + 1;
+        sprite.index =
+            indices.first + (sprite.index.saturating_sub(indices.first) + steps) % frames;
     }
 }
 
+/// The grid and foundations the pile slots are drawn for.
+#[derive(SystemParam)]
+struct BoardLayout<'w> {
+    regions: Res<'w, GridRegions>,
+    foundations: Res<'w, Foundations>,
+}
+
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mut required: ResMut<RequiredAssets>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut images: ResMut<Assets<Image>>,
+    layout: BoardLayout,
+    theme: Res<BackTheme>,
 ) {
-    let texture_handle = asset_server.load("adventurer-sheet.png");
+    // There is no art for card fronts, so faces are a blank card with a text label.
+    let card_assets = CardAssets {
+        front: images.add(blank_card(CARD_FRONT_COLOR)),
+        backs: BackTheme::ALL.map(|theme| required.load(&asset_server, theme.texture())),
+        contrast_front: images.add(blank_card(CONTRAST_FRONT_COLOR)),
+        contrast_back: images.add(blank_card(CONTRAST_BACK_COLOR)),
+    };
+
+    // `reskin_backs` and `apply_contrast_textures` keep the stock in step from
+    // here on.
+    commands.spawn((
+        Stock,
+        RenderLayers::layer(0),
+        SpriteBundle {
+            texture: card_assets.back(*theme),
+            transform: Transform::from_translation(STOCK_POS.extend(0.0)).with_scale(CARD_SIZE),
+            ..default()
+        },
+    ));
+    commands.insert_resource(card_assets);
+
+    let texture_handle = required.load(&asset_server, "adventurer-sheet.png");
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(50.0, 37.0), 7, 10, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -282,20 +6015,26 @@ fn setup(
     let run_animation_indices = AnimationIndices { first: 8, last: 13 };
 
     spawn_player(&mut commands, texture_atlas_handle, run_animation_indices);
-    commands.spawn((
-        Camera2dBundle {
-            camera_2d: Camera2d {
-                // disable clearing completely (pixels stay as they are)
-                // (preserves output from previous frame or camera/pass)
-                clear_color: ClearColorConfig::None,
-            },
-            camera: Camera {
-                order: 1,
-                ..default()
-            },
+    spawn_pile_slots(&layout.regions, &layout.foundations, &mut commands);
+    let cards_camera = Camera2dBundle {
+        camera_2d: Camera2d {
+            // disable clearing completely (pixels stay as they are)
+            // (preserves output from previous frame or camera/pass)
+            clear_color: ClearColorConfig::None,
+        },
+        camera: Camera {
+            order: 1,
             ..default()
         },
-        RenderLayers::from_layers(&[0]),
+        ..default()
+    };
+    commands.insert_resource(CameraDefaults {
+        translation: cards_camera.transform.translation.truncate(),
+        scale: cards_camera.projection.scale,
+    });
+    commands.spawn((
+        cards_camera,
+        RenderLayers::from_layers(&[INTERACTIVE_LAYER, BACKGROUND_LAYER]),
         CardsCamera,
     ));
 
@@ -307,16 +6046,71 @@ fn setup(
             },
             ..default()
         },
+        // The player shares the card layer so the two can be y-sorted; this
+        // camera now only clears the frame for the cards camera.
         RenderLayers::from_layers(&[1]),
+        // UI is drawn once, by the cards camera on top.
+        UiCameraConfig { show_ui: false },
         PlayerCamera,
     ));
+
+    commands
+        .spawn((
+            PauseBanner,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "PAUSED",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// After `timeout` seconds without input the player walks back to `spawn`.
+#[derive(Resource)]
+struct IdleReturn {
+    timeout: f32,
+    spawn: Vec2,
 }
 
+const PLAYER_STEP: f32 = 10.0;
+
 fn move_player_system(
-    mut query: Query<(&mut Transform, &mut AnimationIndices, With<Player>)>,
+    mut query: Query<(
+        &mut Transform,
+        &mut AnimationIndices,
+        &mut Facing,
+        With<Player>,
+    )>,
     keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    idle_return: Res<IdleReturn>,
+    time: Res<Time>,
+    mut idle: Local<f32>,
 ) {
-    let (mut player_transform, mut anim, _) = query.single_mut();
+    let (mut player_transform, mut anim, mut facing, _) = query.single_mut();
+
+    // Any input cancels (and restarts the countdown to) a return.
+    if keys.get_pressed().next().is_some() || buttons.get_pressed().next().is_some() {
+        *idle = 0.0;
+    } else {
+        *idle += time.delta_seconds();
+    }
 
     let mut velocity = Vec2::ZERO;
 
@@ -336,16 +6130,26 @@ fn move_player_system(
         velocity.x = 1.0;
     }
 
+    if velocity == Vec2::ZERO && *idle >= idle_return.timeout {
+        let to_spawn = idle_return.spawn - player_transform.translation.truncate();
+        if to_spawn.length() > PLAYER_STEP {
+            velocity = to_spawn.normalize();
+        } else {
+            player_transform.translation = idle_return.spawn.extend(player_transform.translation.z);
+        }
+    }
+
     *anim = AnimationIndices { first: 8, last: 13 };
-    if velocity.x < 0.0 {
-        player_transform.scale.x = -3.0;
-    } else if velocity.x > 0.0 {
-        player_transform.scale.x = 3.0;
-    } else {
+    if velocity.x == 0.0 {
         *anim = AnimationIndices { first: 0, last: 3 };
     }
 
-    player_transform.translation += (velocity.normalize_or_zero() * 10.0).extend(0.0);
+    // Idle keeps facing the last direction moved.
+    if velocity != Vec2::ZERO {
+        facing.0 = velocity.normalize();
+    }
+
+    player_transform.translation += (velocity.normalize_or_zero() * PLAYER_STEP).extend(0.0);
 }
 
 #[derive(Component)]
@@ -357,6 +6161,19 @@ struct PlayerCamera;
 #[derive(Component)]
 struct Player;
 
+/// Last non-zero movement direction of the player.
+#[derive(Component, Deref)]
+struct Facing(Vec2);
+
+fn face_player(mut query: Query<(&Facing, &mut TextureAtlasSprite), Changed<Facing>>) {
+    for (facing, mut sprite) in &mut query {
+        // Purely vertical movement keeps the previous horizontal flip.
+        if facing.x != 0.0 {
+            sprite.flip_x = facing.x < 0.0;
+        }
+    }
+}
+
 fn spawn_player(
     commands: &mut Commands,
     texture_atlas_handle: Handle<TextureAtlas>,
@@ -371,7 +6188,8 @@ fn spawn_player(
                 .with_translation(vec3(0.0, 0.0, 0.0)),
             ..default()
         },
-        RenderLayers::layer(1),
+        RenderLayers::layer(0),
+        Facing(Vec2::X),
         animation_indices,
         AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
     ));
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::deck::{CardData, Rank};
+
+/// Which card may be placed directly on top of another.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlacementRule {
+    /// Anything goes.
+    Any,
+    /// Tableau style: one rank lower, opposite colour.
+    #[default]
+    AlternatingDescending,
+    /// Foundation style: one rank higher, same suit.
+    SameSuitAscending,
+}
+
+impl PlacementRule {
+    pub fn allows(self, below: &CardData, above: &CardData) -> bool {
+        let below_rank = below.rank.value();
+        let above_rank = above.rank.value();
+
+        match self {
+            PlacementRule::Any => true,
+            PlacementRule::AlternatingDescending => {
+                above_rank + 1 == below_rank && above.suit.is_red() != below.suit.is_red()
+            }
+            PlacementRule::SameSuitAscending => {
+                above_rank == below_rank + 1 && above.suit == below.suit
+            }
+        }
+    }
+
+    /// Whether every consecutive pair, bottom to top, follows the rule.
+    pub fn is_valid_sequence<'a>(self, cards: impl IntoIterator<Item = &'a CardData>) -> bool {
+        let mut cards = cards.into_iter().peekable();
+        while let Some(below) = cards.next() {
+            if let Some(above) = cards.peek() {
+                if !self.allows(below, above) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// The cards from `inbound` a foundation holding `pile` takes, in the order they
+/// stack: the run continuing the pile upwards in its suit, starting from the Ace
+/// on an empty foundation. Whatever doesn't continue the run is left out.
+pub fn foundation_run(pile: &[CardData], inbound: &[CardData]) -> Vec<CardData> {
+    let mut inbound = inbound.to_vec();
+    inbound.sort_by_key(|card| card.rank);
+
+    let mut run = Vec::new();
+    let mut top = pile.last().copied();
+    for card in inbound {
+        let fits = match top {
+            None => card.rank == Rank::Ace,
+            Some(top) => PlacementRule::SameSuitAscending.allows(&top, &card),
+        };
+        if fits {
+            run.push(card);
+            top = Some(card);
+        }
+    }
+    run
+}
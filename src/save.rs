@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use std::{fmt, path::PathBuf};
+
+use crate::{card_id::CardId, deck::CardData, Face};
+
+/// The format `BoardSave::to_text` writes. Older versions are migrated when
+/// they are read.
+///
+/// - 1: `card <id> <card> <x> <y>`
+/// - 2: adds the face showing and any tags: `card <id> <card> <x> <y> <up|down> [tag...]`
+pub const SAVE_VERSION: u32 = 2;
+
+/// Where the console's `save` and `load` commands write and read the board.
+#[derive(Resource)]
+pub struct SaveConfig {
+    pub path: PathBuf,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        SaveConfig {
+            path: PathBuf::from("board.save"),
+        }
+    }
+}
+
+/// Every card on the board, in a line-based text format headed by its
+/// `version`. Cards are listed bottom to top, so piles restack in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardSave {
+    pub version: u32,
+    pub cards: Vec<SavedCard>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedCard {
+    pub id: CardId,
+    pub card: CardData,
+    /// Where the card rests: the cell of its pile, or its own spot if loose.
+    pub position: Vec2,
+    pub face: Face,
+    /// Single words, sorted so saves of the same board are identical.
+    pub tags: Vec<String>,
+}
+
+/// A card as version 1 saved it, before faces and tags were kept.
+struct CardV1 {
+    id: CardId,
+    card: CardData,
+    position: Vec2,
+}
+
+impl CardV1 {
+    /// Every card was face up in version 1, and none had tags.
+    fn migrate(self) -> SavedCard {
+        SavedCard {
+            id: self.id,
+            card: self.card,
+            position: self.position,
+            face: Face::Up,
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveError {
+    MissingVersion,
+    /// Written by a newer build than this one.
+    UnsupportedVersion(u32),
+    BadLine {
+        line: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::MissingVersion => write!(f, "save has no version line"),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "save version {version} is not supported (newest is {SAVE_VERSION})"
+            ),
+            SaveError::BadLine { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl BoardSave {
+    pub fn new(cards: Vec<SavedCard>) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            cards,
+        }
+    }
+
+    /// Always writes the current `SAVE_VERSION`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("version {SAVE_VERSION}\n");
+        for saved in &self.cards {
+            let face = match saved.face {
+                Face::Up => "up",
+                Face::Down => "down",
+            };
+            text.push_str(&format!(
+                "card {} {} {} {} {face}",
+                saved.id.0, saved.card, saved.position.x, saved.position.y
+            ));
+            for tag in &saved.tags {
+                text.push(' ');
+                text.push_str(tag);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Reads a save of any version up to `SAVE_VERSION`, migrated to the current
+    /// one. Blank lines are ignored.
+    pub fn from_text(text: &str) -> Result<Self, SaveError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split_whitespace().collect::<Vec<_>>()))
+            .filter(|(_, words)| !words.is_empty());
+
+        let version = match lines.next() {
+            Some((line, words)) => match words.as_slice() {
+                // Versions count up from 1, so 0 was never written by any build.
+                ["version", version] => version
+                    .parse()
+                    .ok()
+                    .filter(|version| *version > 0)
+                    .ok_or_else(|| SaveError::BadLine {
+                        line,
+                        reason: format!("invalid version {version:?}"),
+                    })?,
+                _ => return Err(SaveError::MissingVersion),
+            },
+            None => return Err(SaveError::MissingVersion),
+        };
+        if version > SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+
+        let cards = lines
+            .map(|(line, words)| {
+                parse_card(version, &words).map_err(|reason| SaveError::BadLine { line, reason })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(cards))
+    }
+}
+
+fn parse_card(version: u32, words: &[&str]) -> Result<SavedCard, String> {
+    let ["card", id, card, x, y, rest @ ..] = words else {
+        return Err("expected card <id> <card> <x> <y>".to_owned());
+    };
+
+    let v1 = CardV1 {
+        id: CardId(id.parse().map_err(|_| format!("invalid card id {id:?}"))?),
+        card: card
+            .parse()
+            .map_err(|err: crate::deck::ParseError| err.to_string())?,
+        position: Vec2::new(
+            x.parse().map_err(|_| format!("invalid x {x:?}"))?,
+            y.parse().map_err(|_| format!("invalid y {y:?}"))?,
+        ),
+    };
+
+    match (version, rest) {
+        (1, []) => Ok(v1.migrate()),
+        (1, _) => Err("unexpected fields after the position".to_owned()),
+        (_, [face, tags @ ..]) => {
+            let face = match *face {
+                "up" => Face::Up,
+                "down" => Face::Down,
+                _ => return Err(format!("invalid face {face:?}")),
+            };
+            Ok(SavedCard {
+                face,
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..v1.migrate()
+            })
+        }
+        (_, []) => Err("expected a face after the position".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::{Rank, Suit};
+
+    fn card(rank: Rank, suit: Suit) -> CardData {
+        CardData {
+            rank,
+            suit,
+            deck: 0,
+        }
+    }
+
+    #[test]
+    fn a_hand_written_v1_save_loads_as_the_current_version() {
+        let text = "version 1\n\
+                    card 0 AS -450 100\n\
+                    \n\
+                    card 7 10H 12.5 -3\n";
+
+        let save = BoardSave::from_text(text).unwrap();
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(
+            save.cards,
+            [
+                SavedCard {
+                    id: CardId(0),
+                    card: card(Rank::Ace, Suit::Spades),
+                    position: Vec2::new(-450.0, 100.0),
+                    face: Face::Up,
+                    tags: Vec::new(),
+                },
+                SavedCard {
+                    id: CardId(7),
+                    card: card(Rank::Ten, Suit::Hearts),
+                    position: Vec2::new(12.5, -3.0),
+                    face: Face::Up,
+                    tags: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_save_reads_back_as_written() {
+        let save = BoardSave::new(vec![
+            SavedCard {
+                id: CardId(3),
+                card: card(Rank::King, Suit::Clubs),
+                position: Vec2::new(150.0, -250.0),
+                face: Face::Down,
+                tags: vec!["bonus".to_owned(), "wild".to_owned()],
+            },
+            SavedCard {
+                id: CardId(4),
+                card: card(Rank::Two, Suit::Diamonds),
+                position: Vec2::ZERO,
+                face: Face::Up,
+                tags: Vec::new(),
+            },
+        ]);
+
+        let text = save.to_text();
+        assert!(text.starts_with(&format!("version {SAVE_VERSION}\n")));
+        assert_eq!(BoardSave::from_text(&text).unwrap(), save);
+    }
+
+    #[test]
+    fn saves_from_a_newer_version_are_refused() {
+        let text = format!("version {}\ncard 0 AS 0 0 up\n", SAVE_VERSION + 1);
+        assert_eq!(
+            BoardSave::from_text(&text),
+            Err(SaveError::UnsupportedVersion(SAVE_VERSION + 1))
+        );
+        assert_eq!(
+            BoardSave::from_text("card 0 AS 0 0 up\n"),
+            Err(SaveError::MissingVersion)
+        );
+        assert!(matches!(
+            BoardSave::from_text("version 0\ncard 0 AS 0 0\n"),
+            Err(SaveError::BadLine { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_by_number() {
+        let text = "version 2\ncard 0 AS 0 0 up\ncard 1 ZZ 0 0 up\n";
+        assert!(matches!(
+            BoardSave::from_text(text),
+            Err(SaveError::BadLine { line: 3, .. })
+        ));
+        // Version 1 had no faces, and version 2 requires one.
+        assert!(BoardSave::from_text("version 1\ncard 0 AS 0 0 up\n").is_err());
+        assert!(BoardSave::from_text("version 2\ncard 0 AS 0 0\n").is_err());
+    }
+}
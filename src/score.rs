@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+
+use crate::loading::AppState;
+
+/// Keeps a running `Score` by summing what every scorer in `Scorers` makes of
+/// each `ScoreEvent`.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScoreEvent>()
+            .init_resource::<Score>()
+            .init_resource::<RoundStart>()
+            .insert_resource(
+                Scorers::default()
+                    .with(PerFoundationCard(10))
+                    .with(PerCompletedSet(100))
+                    .with(TimeBonus {
+                        max: 500,
+                        per_second: 1.0,
+                    }),
+            )
+            .add_systems(Startup, spawn_score_text)
+            .add_systems(OnEnter(AppState::Playing), start_round)
+            .add_systems(Update, (apply_scores, show_score).chain());
+    }
+}
+
+/// Something that happened in play that scorers may reward.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreEvent {
+    CardToFoundation,
+    SetCompleted,
+}
+
+/// What a scorer can see of the game when judging an event.
+pub struct ScoreState {
+    /// Seconds since the round started.
+    pub elapsed: f32,
+}
+
+pub trait Scorer: Send + Sync {
+    /// Points `event` is worth, which may be negative.
+    fn score_event(&self, event: &ScoreEvent, state: &ScoreState) -> i32;
+}
+
+#[derive(Resource, Default)]
+pub struct Scorers(Vec<Box<dyn Scorer>>);
+
+impl Scorers {
+    pub fn with(mut self, scorer: impl Scorer + 'static) -> Self {
+        self.0.push(Box::new(scorer));
+        self
+    }
+
+    /// The sum of every scorer's contribution for `event`.
+    pub fn score_event(&self, event: &ScoreEvent, state: &ScoreState) -> i32 {
+        self.0
+            .iter()
+            .map(|scorer| scorer.score_event(event, state))
+            .sum()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Score(pub i32);
+
+/// When the current round began, in `Time::elapsed_seconds`, so time spent
+/// before play starts doesn't eat into the `TimeBonus`.
+#[derive(Resource, Default)]
+pub struct RoundStart(pub f32);
+
+fn start_round(time: Res<Time>, mut start: ResMut<RoundStart>) {
+    start.0 = time.elapsed_seconds();
+}
+
+/// Points for every card that lands on a foundation.
+pub struct PerFoundationCard(pub i32);
+
+impl Scorer for PerFoundationCard {
+    fn score_event(&self, event: &ScoreEvent, _: &ScoreState) -> i32 {
+        match event {
+            ScoreEvent::CardToFoundation => self.0,
+            _ => 0,
+        }
+    }
+}
+
+/// Points for every foundation built from Ace to King.
+pub struct PerCompletedSet(pub i32);
+
+impl Scorer for PerCompletedSet {
+    fn score_event(&self, event: &ScoreEvent, _: &ScoreState) -> i32 {
+        match event {
+            ScoreEvent::SetCompleted => self.0,
+            _ => 0,
+        }
+    }
+}
+
+/// Up to `max` points for completing a set, less `per_second` for every second
+/// played so far.
+pub struct TimeBonus {
+    pub max: i32,
+    pub per_second: f32,
+}
+
+impl Scorer for TimeBonus {
+    fn score_event(&self, event: &ScoreEvent, state: &ScoreState) -> i32 {
+        match event {
+            ScoreEvent::SetCompleted => {
+                (self.max - (state.elapsed * self.per_second) as i32).max(0)
+            }
+            _ => 0,
+        }
+    }
+}
+
+fn apply_scores(
+    mut events: EventReader<ScoreEvent>,
+    scorers: Res<Scorers>,
+    time: Res<Time>,
+    start: Res<RoundStart>,
+    mut score: ResMut<Score>,
+) {
+    for event in events.read() {
+        let state = ScoreState {
+            elapsed: time.elapsed_seconds() - start.0,
+        };
+        score.0 += scorers.score_event(event, &state);
+    }
+}
+
+#[derive(Component)]
+struct ScoreText;
+
+fn spawn_score_text(mut commands: Commands) {
+    commands.spawn((
+        ScoreText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.0),
+            left: Val::Percent(50.0),
+            ..default()
+        }),
+    ));
+}
+
+fn show_score(score: Res<Score>, mut texts: Query<&mut Text, With<ScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+
+    for mut text in &mut texts {
+        text.sections[0].value = format!("Score: {}", score.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scorers_add_up_their_points_for_each_event() {
+        let scorers = Scorers::default()
+            .with(PerCompletedSet(100))
+            .with(TimeBonus {
+                max: 500,
+                per_second: 2.0,
+            });
+        let at = |elapsed| ScoreState { elapsed };
+
+        assert_eq!(
+            scorers.score_event(&ScoreEvent::SetCompleted, &at(0.0)),
+            600
+        );
+        assert_eq!(
+            scorers.score_event(&ScoreEvent::SetCompleted, &at(50.0)),
+            500
+        );
+        // The bonus runs out rather than going negative.
+        assert_eq!(
+            scorers.score_event(&ScoreEvent::SetCompleted, &at(1000.0)),
+            100
+        );
+        assert_eq!(
+            scorers.score_event(&ScoreEvent::CardToFoundation, &at(0.0)),
+            0
+        );
+    }
+}
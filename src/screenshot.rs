@@ -0,0 +1,89 @@
+use crate::toast::Toast;
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Where F12 screenshots are written.
+#[derive(Resource)]
+pub struct ScreenshotConfig {
+    pub dir: PathBuf,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        ScreenshotConfig {
+            dir: PathBuf::from("screenshots"),
+        }
+    }
+}
+
+/// Results of captures that finished on the render thread, waiting to be
+/// reported.
+#[derive(Resource, Default, Clone)]
+struct FinishedScreenshots(Arc<Mutex<Vec<Result<PathBuf, String>>>>);
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenshotConfig>()
+            .init_resource::<FinishedScreenshots>()
+            .add_systems(Update, (screenshot, report_screenshots));
+    }
+}
+
+/// F12 captures the window. The capture is taken from the window's surface,
+/// so it contains every camera's render layer composited as shown on screen.
+fn screenshot(
+    keys: Res<Input<KeyCode>>,
+    config: Res<ScreenshotConfig>,
+    finished: Res<FinishedScreenshots>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keys.just_pressed(KeyCode::F12) {
+        return;
+    }
+
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let dir = config.dir.clone();
+    let path = dir.join(format!("screenshot-{timestamp}.png"));
+    let finished = finished.0.clone();
+
+    let requested = screenshots.take_screenshot(window, move |image| {
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|err| err.to_string())
+            .and_then(|_| image.try_into_dynamic().map_err(|err| err.to_string()))
+            .and_then(|image| image.to_rgb8().save(&path).map_err(|err| err.to_string()))
+            .map(|_| path);
+
+        finished.lock().unwrap().push(result);
+    });
+
+    if requested.is_err() {
+        toasts.send(Toast("Screenshot already in progress".to_owned()));
+    }
+}
+
+fn report_screenshots(finished: Res<FinishedScreenshots>, mut toasts: EventWriter<Toast>) {
+    let mut finished = finished.0.lock().unwrap();
+
+    for result in finished.drain(..) {
+        let message = match result {
+            Ok(path) => format!("Saved {}", path.display()),
+            Err(err) => format!("Screenshot failed: {err}"),
+        };
+        toasts.send(Toast(message));
+    }
+}
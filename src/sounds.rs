@@ -0,0 +1,124 @@
+use bevy::{asset::io::file::FileAssetReader, prelude::*, utils::HashMap};
+
+use crate::{CardDropped, CardPickedUp, Tags};
+
+/// Plays a sound whenever a card is picked up or dropped. Tagged cards can have
+/// a sound of their own through `TagSounds`.
+///
+/// The files under `assets/sounds` are optional. Any that is missing is never
+/// loaded, and what it would have played passes silently.
+pub struct SoundsPlugin;
+
+impl Plugin for SoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sounds)
+            .add_systems(Update, play_card_sounds);
+    }
+}
+
+/// Default sounds for cards without a tag in `TagSounds`, if their files exist.
+#[derive(Resource)]
+pub struct CardSounds {
+    pub pickup: Option<Handle<AudioSource>>,
+    pub drop: Option<Handle<AudioSource>>,
+}
+
+/// A sound per tag, played for both pickup and drop in place of `CardSounds`.
+#[derive(Resource, Default)]
+pub struct TagSounds(HashMap<String, Handle<AudioSource>>);
+
+impl TagSounds {
+    pub fn insert(&mut self, tag: impl Into<String>, sound: Handle<AudioSource>) {
+        self.0.insert(tag.into(), sound);
+    }
+
+    /// The sound for a card with `tags`. A card with several sounding tags uses
+    /// the alphabetically first, so it's the same sound every time.
+    pub fn for_tags(&self, tags: &Tags) -> Option<&Handle<AudioSource>> {
+        tags.iter()
+            .filter(|tag| self.0.contains_key(*tag))
+            .min()
+            .and_then(|tag| self.0.get(tag))
+    }
+}
+
+/// Starts loading the sound at `path` under `assets`, unless there's no such file.
+fn load_if_present(asset_server: &AssetServer, path: &str) -> Option<Handle<AudioSource>> {
+    let file = FileAssetReader::get_base_path().join("assets").join(path);
+    file.is_file().then(|| asset_server.load(path.to_owned()))
+}
+
+fn load_sounds(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(CardSounds {
+        pickup: load_if_present(&asset_server, "sounds/pickup.ogg"),
+        drop: load_if_present(&asset_server, "sounds/drop.ogg"),
+    });
+
+    let mut tag_sounds = TagSounds::default();
+    if let Some(metal) = load_if_present(&asset_server, "sounds/metal.ogg") {
+        tag_sounds.insert("metal", metal);
+    }
+    commands.insert_resource(tag_sounds);
+}
+
+fn play_card_sounds(
+    sounds: Res<CardSounds>,
+    tag_sounds: Res<TagSounds>,
+    tags: Query<&Tags>,
+    mut picked_up: EventReader<CardPickedUp>,
+    mut dropped: EventReader<CardDropped>,
+    mut commands: Commands,
+) {
+    let events = picked_up
+        .read()
+        .map(|CardPickedUp(entity)| (*entity, &sounds.pickup))
+        .chain(
+            dropped
+                .read()
+                .map(|CardDropped(entity)| (*entity, &sounds.drop)),
+        );
+
+    for (entity, default) in events {
+        let Some(source) = tags
+            .get(entity)
+            .ok()
+            .and_then(|tags| tag_sounds.for_tags(tags))
+            .or(default.as_ref())
+        else {
+            continue;
+        };
+
+        commands.spawn(AudioBundle {
+            source: source.clone(),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Tags {
+        Tags(tags.iter().map(|tag| tag.to_string()).collect())
+    }
+
+    #[test]
+    fn tagged_cards_get_their_tags_sound() {
+        let metal = Handle::weak_from_u128(1);
+        let glass = Handle::weak_from_u128(2);
+        let mut tag_sounds = TagSounds::default();
+        tag_sounds.insert("metal", metal.clone());
+        tag_sounds.insert("glass", glass.clone());
+
+        assert_eq!(tag_sounds.for_tags(&tags(&["metal"])), Some(&metal));
+        assert_eq!(tag_sounds.for_tags(&tags(&["wild", "metal"])), Some(&metal));
+        // Alphabetically first of the tags with a sound.
+        assert_eq!(
+            tag_sounds.for_tags(&tags(&["metal", "glass"])),
+            Some(&glass)
+        );
+        assert_eq!(tag_sounds.for_tags(&tags(&["wild"])), None);
+        assert_eq!(tag_sounds.for_tags(&tags(&[])), None);
+    }
+}
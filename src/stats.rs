@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::{
+    deck::{Deck, Suit},
+    Card, Face,
+};
+
+/// F7 shows how many of each suit are left in the deck and how many cards on
+/// the board are face up or down.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_stats_panel)
+            .add_systems(Update, (toggle_stats, deck_stats).chain());
+    }
+}
+
+#[derive(Component)]
+struct StatsPanel;
+
+fn spawn_stats_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            StatsPanel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    left: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn toggle_stats(keys: Res<Input<KeyCode>>, mut panels: Query<&mut Visibility, With<StatsPanel>>) {
+    if !keys.just_pressed(KeyCode::F7) {
+        return;
+    }
+
+    for mut visibility in &mut panels {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Recounts only when the deck changes, a card is added or removed, a card
+/// flips, or the panel is shown.
+fn deck_stats(
+    deck: Res<Deck>,
+    cards: Query<Option<&Face>, With<Card>>,
+    added: Query<(), Added<Card>>,
+    flipped: Query<(), Changed<Face>>,
+    mut removed: RemovedComponents<Card>,
+    panels: Query<(Ref<Visibility>, &Children), With<StatsPanel>>,
+    mut texts: Query<&mut Text>,
+) {
+    let shown = panels.iter().any(|(visibility, _)| visibility.is_changed());
+    let board_changed = !added.is_empty() || !flipped.is_empty() || removed.read().count() > 0;
+    if !(deck.is_changed() || board_changed || shown) {
+        return;
+    }
+
+    let mut stats = "Deck:".to_string();
+    for suit in Suit::ALL {
+        let left = deck.iter().filter(|card| card.suit == suit).count();
+        stats.push_str(&format!("\n  {suit:?}: {left}"));
+    }
+
+    // Cards without a face only ever show their back.
+    let face_up = cards.iter().filter(|face| *face == Some(&Face::Up)).count();
+    let face_down = cards.iter().count() - face_up;
+    stats.push_str(&format!(
+        "\nBoard:\n  Face up: {face_up}\n  Face down: {face_down}"
+    ));
+
+    for (_, children) in &panels {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = stats.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,2555 @@
+//! Headless tests of whole systems: each builds an app on `MinimalPlugins`
+//! with the board's resources, adds only the systems under test and steps it
+//! frame by frame with a fixed clock.
+
+use super::*;
+use crate::{deck::Suit, inspector::inspect_card};
+use bevy::{
+    ecs::system::CommandQueue,
+    input::touch::{touch_screen_input_system, TouchPhase},
+    render::camera::{camera_system, CameraUpdateSystem, ManualTextureViews},
+    time::TimeUpdateStrategy,
+    window::{PrimaryWindow, WindowCreated, WindowResolution},
+};
+use std::time::Duration;
+
+/// How far the clock moves on every update.
+const FRAME: Duration = Duration::from_millis(16);
+
+/// Frames a dropped card is given to settle.
+const SETTLE_FRAMES: usize = 60;
+
+/// An app with every board resource and event, blank card textures, and no
+/// systems of its own.
+fn board_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, BoardPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .init_resource::<Input<MouseButton>>()
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<Assets<Image>>()
+        .add_event::<Toast>()
+        .add_event::<ScoreEvent>()
+        .add_systems(PostUpdate, apply_card_layer);
+
+    let mut images = app.world.resource_mut::<Assets<Image>>();
+    let front = images.add(blank_card(CARD_FRONT_COLOR));
+    let backs = BackTheme::ALL.map(|_| images.add(blank_card([40, 40, 160, 255])));
+    let contrast_front = images.add(blank_card(CONTRAST_FRONT_COLOR));
+    let contrast_back = images.add(blank_card(CONTRAST_BACK_COLOR));
+    app.insert_resource(CardAssets {
+        front,
+        backs,
+        contrast_front,
+        contrast_back,
+    });
+    app
+}
+
+/// The select, drag and drop systems in the order they run in the game.
+fn add_drag_systems(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_cursor,
+            update_bounds,
+            select_card,
+            drag_selected,
+            finish_drag_selected,
+            align_placed,
+            index_piles,
+        )
+            .chain(),
+    );
+}
+
+/// Runs one frame, then lets go of this frame's presses and releases the way
+/// the input plugin would at the start of the next.
+fn step(app: &mut App) {
+    app.update();
+    app.world.resource_mut::<Input<MouseButton>>().clear();
+    app.world.resource_mut::<Input<KeyCode>>().clear();
+}
+
+fn step_frames(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        step(app);
+    }
+}
+
+fn move_pointer(app: &mut App, point: Vec2) {
+    app.world.resource_mut::<PointerState>().world = Some(point);
+}
+
+fn press(app: &mut App, button: MouseButton) {
+    app.world.resource_mut::<Input<MouseButton>>().press(button);
+}
+
+fn release(app: &mut App, button: MouseButton) {
+    app.world
+        .resource_mut::<Input<MouseButton>>()
+        .release(button);
+}
+
+/// Every card `CardDropped` was sent for, since events only last two frames.
+#[derive(Resource, Default)]
+struct Dropped(Vec<Entity>);
+
+fn record_drops(mut events: EventReader<CardDropped>, mut dropped: ResMut<Dropped>) {
+    dropped
+        .0
+        .extend(events.read().map(|CardDropped(entity)| *entity));
+}
+
+fn sorted(deck: &Deck) -> Vec<CardData> {
+    let mut cards = deck.iter().copied().collect::<Vec<_>>();
+    cards.sort_by_key(|card| (card.suit, card.rank));
+    cards
+}
+
+fn press_key(app: &mut App, key: KeyCode) {
+    app.world.resource_mut::<Input<KeyCode>>().press(key);
+}
+
+fn card_count(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<Card>>()
+        .iter(&app.world)
+        .count()
+}
+
+/// Toasts sent during the last update.
+fn toasts(app: &App) -> Vec<String> {
+    app.world
+        .resource::<Events<Toast>>()
+        .iter_current_update_events()
+        .map(|Toast(message)| message.clone())
+        .collect()
+}
+
+fn card(rank: Rank, suit: Suit) -> CardData {
+    CardData {
+        rank,
+        suit,
+        deck: 0,
+    }
+}
+
+/// Spawns `card` at `pos` the way the game does and lets it land in its pile.
+fn spawn_at(app: &mut App, pos: Vec2, card: CardData, face: Face) -> Entity {
+    let mut queue = CommandQueue::default();
+    let world = &app.world;
+    let entity = spawn_card_at(
+        &mut Commands::new(&mut queue, world),
+        world.resource::<CardAssets>(),
+        pos,
+        card,
+        face,
+    );
+    queue.apply(&mut app.world);
+    entity
+}
+
+/// Spawns `cards` fanned out in the pile at `pos`, bottom first.
+fn spawn_fan(app: &mut App, pos: Vec2, cards: &[CardData]) -> Vec<Entity> {
+    cards
+        .iter()
+        .enumerate()
+        .map(|(slot, card)| {
+            let entity = spawn_at(app, pos, *card, Face::Up);
+            app.world
+                .entity_mut(entity)
+                .insert(FanSlot(slot, cards.len()));
+            entity
+        })
+        .collect()
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world.get::<Transform>(entity).unwrap().translation.xy()
+}
+
+fn pile_of(app: &App, entity: Entity) -> Option<Pile> {
+    app.world.get::<Pile>(entity).copied()
+}
+
+fn pile_at(app: &App, pos: Vec2) -> Vec<Entity> {
+    let pile = Pile::new(align_grid(app.world.resource::<GridRegions>(), pos));
+    app.world
+        .resource::<PileIndex>()
+        .get(&pile)
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn selected_card_is_dragged_and_dropped_onto_a_pile() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.init_resource::<Dropped>()
+        .add_systems(Last, record_drops);
+
+    let regions = GridRegions::split_board();
+    let from = tableau_pile_pos(&regions, 0);
+    let to = tableau_pile_pos(&regions, 2);
+    let base = spawn_at(&mut app, to, card(Rank::Nine, Suit::Spades), Face::Up);
+    let carried = spawn_at(&mut app, from, card(Rank::Eight, Suit::Hearts), Face::Up);
+    move_pointer(&mut app, from);
+    step_frames(&mut app, SETTLE_FRAMES);
+    app.world.resource_mut::<Dropped>().0.clear();
+    assert_eq!(pile_at(&app, from), vec![carried]);
+    assert_eq!(pile_at(&app, to), vec![base]);
+
+    // Select.
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Selected>(carried).is_some());
+    assert_eq!(pile_of(&app, carried), None);
+
+    // Drag: the card follows the pointer across the board.
+    for i in 1..=10 {
+        move_pointer(&mut app, from.lerp(to, i as f32 / 10.0));
+        step(&mut app);
+    }
+    assert!(pile_at(&app, from).is_empty());
+    step_frames(&mut app, 20);
+    let held = app
+        .world
+        .get::<Transform>(carried)
+        .unwrap()
+        .translation
+        .xy();
+    assert!(held.distance(to) < 15.0, "{held} should be near {to}");
+    assert!(app.world.get::<Selected>(carried).is_some());
+
+    // Drop, and it lands on the pile under the pointer.
+    release(&mut app, MouseButton::Left);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert!(app.world.get::<Selected>(carried).is_none());
+    assert!(app.world.get::<Dragging>(carried).is_none());
+    assert_eq!(pile_of(&app, carried), pile_of(&app, base));
+    assert_eq!(pile_at(&app, to), vec![base, carried]);
+    assert_eq!(app.world.resource::<Dropped>().0, vec![carried]);
+}
+
+#[test]
+fn reshuffling_reorders_the_same_cards() {
+    let mut app = board_app();
+    app.insert_resource(InputBuffer::new(1.0))
+        .add_event::<Reshuffled>()
+        .add_systems(Update, reshuffle);
+    let before = app.world.resource::<Deck>().clone();
+
+    app.world
+        .resource_mut::<InputBuffer>()
+        .press(Action::Shuffle, 0.0);
+    step(&mut app);
+
+    let after = app.world.resource::<Deck>();
+    assert_eq!(after.len(), before.len());
+    assert!(after.iter().ne(before.iter()));
+    assert_eq!(sorted(after), sorted(&before));
+    assert_eq!(app.world.resource::<Events<Reshuffled>>().len(), 1);
+}
+
+#[test]
+fn despawned_cards_leave_nothing_behind() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_plugins(CardIdPlugin)
+        .init_resource::<Inspected>()
+        .add_systems(PostUpdate, despawn_cards);
+
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 0);
+    let bottom = spawn_at(&mut app, pos, card(Rank::Two, Suit::Clubs), Face::Up);
+    let top = spawn_at(&mut app, pos, card(Rank::Three, Suit::Clubs), Face::Up);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(pile_at(&app, pos), vec![bottom, top]);
+
+    let id = *app.world.get::<CardId>(top).unwrap();
+    let children = app.world.get::<Children>(top).unwrap().to_vec();
+    assert!(!children.is_empty());
+    app.world.resource_mut::<Inspected>().0 = Some(top);
+    app.world
+        .resource_mut::<ClickTracker>()
+        .0
+        .insert(top, (0.0, pos));
+    app.world
+        .resource_mut::<CameraFocus>()
+        .set_target(FocusTarget::Entity(top));
+
+    app.world.send_event(DespawnCard(top));
+    step(&mut app);
+
+    assert!(app.world.get_entity(top).is_none());
+    for child in children {
+        assert!(app.world.get_entity(child).is_none());
+    }
+    let mut parents = app.world.query::<&Parent>();
+    for parent in parents.iter(&app.world) {
+        assert!(app.world.get_entity(parent.get()).is_some());
+    }
+    assert_eq!(pile_at(&app, pos), vec![bottom]);
+    assert_eq!(app.world.resource::<Inspected>().0, None);
+    assert!(!app.world.resource::<ClickTracker>().0.contains_key(&top));
+    assert!(app.world.resource::<CameraFocus>().target == FocusTarget::Free);
+    assert_eq!(app.world.resource::<CardIds>().get(id), None);
+}
+
+#[test]
+fn sprite_animation_steps_with_the_animation_speed() {
+    // Steps of 0.1s in 105 frames of 16ms at each speed.
+    for (speed, steps) in [(0.0, 0), (0.5, 8), (1.0, 16), (1.5, 25), (4.0, 67)] {
+        let mut app = board_app();
+        app.insert_resource(AnimationSpeed(speed))
+            .add_systems(Update, animate_sprite);
+        let indices = AnimationIndices { first: 8, last: 13 };
+        let first = indices.first;
+        let sprite = app
+            .world
+            .spawn((
+                indices,
+                AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+                TextureAtlasSprite::new(first),
+            ))
+            .id();
+
+        // The clock starts on the first update, so it doesn't move the timer.
+        step_frames(&mut app, 1 + 105);
+
+        let index = app.world.get::<TextureAtlasSprite>(sprite).unwrap().index;
+        assert_eq!(index, first + steps % 6, "at speed {speed}");
+    }
+}
+
+#[test]
+fn timers_stand_still_while_paused() {
+    let mut app = board_app();
+    app.add_systems(
+        Update,
+        (animate_sprite, fade_disappearing).run_if(not(paused)),
+    );
+    let entity = app
+        .world
+        .spawn((
+            AnimationIndices { first: 0, last: 5 },
+            AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+            TextureAtlasSprite::new(0),
+            Sprite::default(),
+            Disappearing(Timer::from_seconds(10.0, TimerMode::Once)),
+        ))
+        .id();
+    let elapsed = |app: &App| {
+        (
+            app.world
+                .get::<AnimationTimer>(entity)
+                .unwrap()
+                .elapsed_secs(),
+            app.world
+                .get::<Disappearing>(entity)
+                .unwrap()
+                .elapsed_secs(),
+        )
+    };
+    let frame = FRAME.as_secs_f32();
+
+    step_frames(&mut app, 1 + 5);
+    let (animation, fade) = elapsed(&app);
+    assert!((animation - 5.0 * frame).abs() < 1e-4);
+    assert!((fade - 5.0 * frame).abs() < 1e-4);
+
+    app.world.resource_mut::<Paused>().0 = true;
+    step_frames(&mut app, 50);
+    assert_eq!(elapsed(&app), (animation, fade));
+
+    // Unpausing picks up with one frame's delta, not the paused stretch.
+    app.world.resource_mut::<Paused>().0 = false;
+    step(&mut app);
+    let (animation, fade) = elapsed(&app);
+    assert!((animation - 6.0 * frame).abs() < 1e-4);
+    assert!((fade - 6.0 * frame).abs() < 1e-4);
+}
+
+/// Every `ScoreEvent` sent, and whether all the cards of `watched` were still
+/// around when it was.
+#[derive(Resource, Default)]
+struct Scored {
+    watched: Vec<Entity>,
+    events: Vec<(ScoreEvent, bool)>,
+}
+
+fn record_scores(
+    mut events: EventReader<ScoreEvent>,
+    cards: Query<(), With<Card>>,
+    mut scored: ResMut<Scored>,
+) {
+    for event in events.read() {
+        let present = scored.watched.iter().all(|entity| cards.contains(*entity));
+        scored.events.push((*event, present));
+    }
+}
+
+#[test]
+fn completed_sets_are_scored_then_leave_by_their_animation() {
+    let frames = (COMPLETE_DURATION / FRAME.as_secs_f32()).ceil() as usize;
+
+    for animation in [
+        CompleteAnimation::FlyOff,
+        CompleteAnimation::FadeOut,
+        CompleteAnimation::Shrink,
+    ] {
+        let mut app = board_app();
+        app.insert_resource(animation)
+            .init_resource::<CardIds>()
+            .init_resource::<Inspected>()
+            .init_resource::<Scored>()
+            .add_systems(
+                Update,
+                (
+                    report_score_events,
+                    clear_completed_set,
+                    animate_completing,
+                    fade_disappearing,
+                )
+                    .chain(),
+            )
+            .add_systems(PostUpdate, despawn_cards)
+            .add_systems(Last, record_scores);
+
+        let pos = Foundations::top_right(&GridRegions::split_board())[0].center();
+        let set = Rank::ALL
+            .map(|rank| spawn_at(&mut app, pos, card(rank, Suit::Hearts), Face::Up))
+            .to_vec();
+        step(&mut app);
+        let start = *app.world.get::<Transform>(set[0]).unwrap();
+        app.world.resource_mut::<Scored>().watched = set.clone();
+
+        app.world.send_event(SetCompleted(set.clone()));
+        step_frames(&mut app, frames / 2);
+        assert_eq!(
+            app.world.resource::<Scored>().events,
+            vec![(ScoreEvent::SetCompleted, true)]
+        );
+
+        let midway = *app.world.get::<Transform>(set[0]).unwrap();
+        let alpha = app.world.get::<Sprite>(set[0]).unwrap().color.a();
+        assert!(app.world.get::<Pile>(set[0]).is_none());
+        match animation {
+            CompleteAnimation::FlyOff => {
+                assert!(midway.translation.y > start.translation.y);
+                assert_eq!(alpha, 1.0);
+            }
+            CompleteAnimation::FadeOut => {
+                assert!(alpha < 1.0);
+                assert_eq!(midway.translation, start.translation);
+            }
+            CompleteAnimation::Shrink => {
+                assert!(midway.scale.x < start.scale.x);
+                assert_eq!(alpha, 1.0);
+            }
+            CompleteAnimation::Display => unreachable!(),
+        }
+
+        step_frames(&mut app, frames);
+        for entity in &set {
+            assert!(app.world.get_entity(*entity).is_none(), "{animation:?}");
+        }
+    }
+}
+
+fn spawn_window(app: &mut App, width: f32, height: f32) -> Entity {
+    app.world
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(width, height),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id()
+}
+
+fn resize_window(app: &mut App, window: Entity, width: f32, height: f32) {
+    app.world
+        .get_mut::<Window>(window)
+        .unwrap()
+        .resolution
+        .set(width, height);
+    app.world.send_event(WindowResized {
+        window,
+        width,
+        height,
+    });
+}
+
+/// Gives the app a primary window and the cards camera, and keeps the camera's
+/// viewport up to date the way the render plugin would, so cursor mapping
+/// works.
+fn add_cards_camera(app: &mut App, width: f32, height: f32) -> (Entity, Entity) {
+    app.add_plugins(TransformPlugin)
+        .init_resource::<ManualTextureViews>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowResized>()
+        .add_event::<AssetEvent<Image>>()
+        .add_systems(
+            PostUpdate,
+            camera_system::<OrthographicProjection>.in_set(CameraUpdateSystem),
+        );
+    let window = spawn_window(app, width, height);
+    let camera = app
+        .world
+        .spawn((Camera2dBundle::default(), CardsCamera))
+        .id();
+    (window, camera)
+}
+
+fn move_cursor(app: &mut App, window: Entity, position: Vec2) {
+    app.world
+        .get_mut::<Window>(window)
+        .unwrap()
+        .set_cursor_position(Some(position));
+}
+
+/// How far, in screen pixels, the discard zone sits from the bottom right
+/// corner of the window, as drawn by `camera`.
+fn discard_zone_corner_gap(app: &App, camera: Entity, width: f32, height: f32) -> Vec2 {
+    let zone = app.world.resource::<DiscardZone>().0;
+    let scale = app
+        .world
+        .get::<OrthographicProjection>(camera)
+        .unwrap()
+        .scale;
+    let (camera, transform) = (
+        app.world.get::<Camera>(camera).unwrap(),
+        app.world.get::<GlobalTransform>(camera).unwrap(),
+    );
+    let center = camera
+        .world_to_viewport(transform, zone.center().extend(0.0))
+        .unwrap();
+    vec2(width, height) - center - zone.size() / scale / 2.0
+}
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    assert!(actual.distance(expected) < 0.01, "{actual} != {expected}");
+}
+
+#[test]
+fn discard_zone_stays_in_the_corner_when_the_window_resizes() {
+    let mut app = board_app();
+    let (window, camera) = add_cards_camera(&mut app, 800.0, 600.0);
+    app.add_systems(
+        PostUpdate,
+        anchor_discard_zone
+            .after(CameraUpdateSystem)
+            .after(TransformSystem::TransformPropagate),
+    );
+
+    step(&mut app);
+    assert_near(
+        discard_zone_corner_gap(&app, camera, 800.0, 600.0),
+        Vec2::splat(DISCARD_ZONE_MARGIN),
+    );
+
+    resize_window(&mut app, window, 1200.0, 900.0);
+    step(&mut app);
+    assert_near(
+        discard_zone_corner_gap(&app, camera, 1200.0, 900.0),
+        Vec2::splat(DISCARD_ZONE_MARGIN),
+    );
+    assert_eq!(
+        app.world.resource::<DiscardZone>().size(),
+        DISCARD_ZONE_SIZE
+    );
+}
+
+#[test]
+fn discard_zone_follows_the_camera_as_it_pans_zooms_and_rotates() {
+    let mut app = board_app();
+    let (_, camera) = add_cards_camera(&mut app, 800.0, 600.0);
+    app.add_systems(
+        PostUpdate,
+        anchor_discard_zone
+            .after(CameraUpdateSystem)
+            .after(TransformSystem::TransformPropagate),
+    );
+    step(&mut app);
+
+    app.world.get_mut::<Transform>(camera).unwrap().translation = vec3(300.0, -150.0, 0.0);
+    step(&mut app);
+    assert_near(
+        discard_zone_corner_gap(&app, camera, 800.0, 600.0),
+        Vec2::splat(DISCARD_ZONE_MARGIN),
+    );
+
+    app.world
+        .get_mut::<OrthographicProjection>(camera)
+        .unwrap()
+        .scale = MAX_ZOOM;
+    step(&mut app);
+    assert_near(
+        discard_zone_corner_gap(&app, camera, 800.0, 600.0),
+        Vec2::splat(DISCARD_ZONE_MARGIN),
+    );
+    assert_near(
+        app.world.resource::<DiscardZone>().size(),
+        DISCARD_ZONE_SIZE * MAX_ZOOM,
+    );
+
+    app.world.get_mut::<Transform>(camera).unwrap().rotation = Quat::from_rotation_z(PI);
+    step(&mut app);
+    assert_near(
+        discard_zone_corner_gap(&app, camera, 800.0, 600.0),
+        Vec2::splat(DISCARD_ZONE_MARGIN),
+    );
+}
+
+#[test]
+fn camera_is_held_on_the_board_centre_at_max_zoom_out() {
+    let mut app = board_app();
+    let (_, camera) = add_cards_camera(&mut app, 1280.0, 720.0);
+    app.add_systems(Update, clamp_camera);
+    app.world.get_mut::<Transform>(camera).unwrap().translation = vec3(400.0, -300.0, 0.0);
+    app.world
+        .get_mut::<OrthographicProjection>(camera)
+        .unwrap()
+        .scale = MAX_ZOOM;
+    step(&mut app);
+
+    let center = app.world.resource::<CameraBounds>().center();
+    let translation = app.world.get::<Transform>(camera).unwrap().translation;
+    assert_eq!(translation.truncate(), center);
+
+    // Zoomed in, the same spot is within reach.
+    app.world
+        .get_mut::<OrthographicProjection>(camera)
+        .unwrap()
+        .scale = 1.0;
+    app.world.get_mut::<Transform>(camera).unwrap().translation = vec3(100.0, -100.0, 0.0);
+    step(&mut app);
+    let translation = app.world.get::<Transform>(camera).unwrap().translation;
+    assert_eq!(translation.truncate(), vec2(100.0, -100.0));
+}
+
+#[test]
+fn dealing_stops_at_the_card_limit() {
+    let mut app = board_app();
+    app.insert_resource(MaxCards(5))
+        .add_systems(Update, deal_hand);
+    for rank in [Rank::Ace, Rank::Two, Rank::Three] {
+        spawn_at(&mut app, Vec2::ZERO, card(rank, Suit::Spades), Face::Up);
+    }
+
+    // Only the two cards below the limit are dealt.
+    app.world.send_event(DealHand(4));
+    step(&mut app);
+    assert_eq!(card_count(&mut app), 5);
+    assert_eq!(app.world.resource::<Deck>().len(), STANDARD_DECK_SIZE - 2);
+    assert!(toasts(&app).is_empty());
+
+    app.world.send_event(DealHand(1));
+    step(&mut app);
+    assert_eq!(card_count(&mut app), 5);
+    assert_eq!(toasts(&app), vec!["Card limit of 5 reached".to_owned()]);
+}
+
+#[test]
+fn spawning_stops_at_the_card_limit() {
+    let mut app = board_app();
+    app.insert_resource(MaxCards(2))
+        .insert_resource(SpawnKey(Some(KeyCode::N)))
+        .add_systems(Update, create_card);
+    spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Ace, Suit::Spades),
+        Face::Up,
+    );
+
+    press_key(&mut app, KeyCode::N);
+    step(&mut app);
+    assert_eq!(card_count(&mut app), 2);
+    assert!(toasts(&app).is_empty());
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::N);
+    press_key(&mut app, KeyCode::N);
+    step(&mut app);
+    assert_eq!(card_count(&mut app), 2);
+    assert_eq!(toasts(&app), vec!["Card limit of 2 reached".to_owned()]);
+}
+
+#[test]
+fn dragged_fan_lands_as_the_same_fan() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+
+    let regions = GridRegions::split_board();
+    let from = tableau_pile_pos(&regions, 0);
+    let to = tableau_pile_pos(&regions, 2);
+    let ranks = [Rank::Nine, Rank::Eight, Rank::Seven, Rank::Six, Rank::Five];
+    let fan = spawn_fan(&mut app, from, &ranks.map(|rank| card(rank, Suit::Clubs)));
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(pile_at(&app, from), fan);
+
+    // Grab the whole substack by the corner only the bottom card shows.
+    let grab = from - Vec2::new(40.0, 60.0);
+    move_pointer(&mut app, grab);
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    for entity in &fan {
+        assert!(app.world.get::<Selected>(*entity).is_some());
+    }
+
+    for i in 1..=10 {
+        move_pointer(&mut app, grab.lerp(to, i as f32 / 10.0));
+        step(&mut app);
+    }
+    release(&mut app, MouseButton::Left);
+    step_frames(&mut app, SETTLE_FRAMES);
+
+    let extent = app.world.resource::<MaxPileExtent>().0;
+    assert_eq!(pile_at(&app, to), fan);
+    for (slot, entity) in fan.iter().enumerate() {
+        let expected = to + pile_offset_for(slot, fan.len(), extent);
+        let landed = position(&app, *entity);
+        assert!(landed.distance(expected) < 1.0, "{landed} != {expected}");
+    }
+}
+
+#[test]
+fn clicks_hit_the_card_under_the_cursor_after_rotating_the_board() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_systems(
+        Update,
+        (rotate_board_view, read_pointer.before(update_cursor)),
+    );
+    let (window, _) = add_cards_camera(&mut app, 1280.0, 720.0);
+
+    let regions = GridRegions::split_board();
+    let left = spawn_at(
+        &mut app,
+        tableau_pile_pos(&regions, 0),
+        card(Rank::Ace, Suit::Spades),
+        Face::Up,
+    );
+    let right = spawn_at(
+        &mut app,
+        tableau_pile_pos(&regions, 6),
+        card(Rank::King, Suit::Hearts),
+        Face::Up,
+    );
+    press_key(&mut app, KeyCode::V);
+    step_frames(&mut app, SETTLE_FRAMES);
+    let camera = app
+        .world
+        .query_filtered::<&Transform, With<CardsCamera>>()
+        .single(&app.world)
+        .rotation;
+    assert_eq!(camera, Quat::from_rotation_z(PI));
+
+    // Turned half way round, the right card shows where the left one did.
+    let point = position(&app, right);
+    move_cursor(&mut app, window, vec2(640.0 - point.x, 360.0 + point.y));
+    step(&mut app);
+    assert!(app.world.resource::<WordCursor>().distance(point) < 0.01);
+
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Selected>(right).is_some());
+    assert!(app.world.get::<Selected>(left).is_none());
+}
+
+#[test]
+fn pickup_mode_decides_how_much_of_a_pile_is_picked_up() {
+    for (mode, picked) in [(PickupMode::Substack, 1..4), (PickupMode::TopOnly, 3..4)] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(mode)
+            .add_systems(Update, ysort.after(index_piles));
+
+        let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+        let ranks = [Rank::Eight, Rank::Seven, Rank::Six, Rank::Five];
+        let pile = spawn_fan(&mut app, pos, &ranks.map(|rank| card(rank, Suit::Hearts)));
+        step_frames(&mut app, SETTLE_FRAMES);
+
+        // A sliver of the second card, between the first and the third.
+        move_pointer(&mut app, pos + vec2(-33.0, -56.0));
+        press(&mut app, MouseButton::Left);
+        step(&mut app);
+
+        for (i, entity) in pile.iter().enumerate() {
+            let selected = app.world.get::<Selected>(*entity).is_some();
+            assert_eq!(selected, picked.contains(&i), "card {i} with {mode:?}");
+        }
+    }
+}
+
+#[test]
+fn flipping_swaps_the_texture_edge_on() {
+    let mut app = board_app();
+    app.add_systems(Update, flip_animation);
+    let entity = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Queen, Suit::Spades),
+        Face::Down,
+    );
+    let assets = app.world.resource::<CardAssets>();
+    let (front, back) = (assets.front.clone(), assets.back(BackTheme::default()));
+    step(&mut app);
+
+    for (from, to, from_texture, to_texture) in [
+        (Face::Down, Face::Up, &back, &front),
+        (Face::Up, Face::Down, &front, &back),
+    ] {
+        app.world.entity_mut(entity).insert(FlipAnim { t: 0.0, to });
+        let mut seen = (false, false);
+        while let Some(t) = app.world.get::<FlipAnim>(entity).map(|flip| flip.t) {
+            let texture = app.world.get::<Handle<Image>>(entity).unwrap();
+            let face = *app.world.get::<Face>(entity).unwrap();
+            if t < 0.5 {
+                assert_eq!((texture, face), (from_texture, from), "at t = {t}");
+                seen.0 = true;
+            } else {
+                assert_eq!((texture, face), (to_texture, to), "at t = {t}");
+                seen.1 = true;
+            }
+            step(&mut app);
+        }
+        assert_eq!(seen, (true, true));
+        assert_eq!(app.world.get::<Handle<Image>>(entity), Some(to_texture));
+    }
+}
+
+#[test]
+fn face_down_backs_are_tinted_by_their_deck_not_their_suit() {
+    let mut app = board_app();
+    app.add_systems(Update, apply_back_tint);
+    app.world.resource_mut::<BackTints>().enabled = true;
+
+    let spade = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Ace, Suit::Spades),
+        Face::Down,
+    );
+    let heart = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Two, Suit::Hearts),
+        Face::Down,
+    );
+    let second_spade = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        CardData {
+            deck: 1,
+            ..card(Rank::Ace, Suit::Spades)
+        },
+        Face::Down,
+    );
+    let face_up = spawn_at(&mut app, Vec2::ZERO, card(Rank::Ace, Suit::Clubs), Face::Up);
+    step(&mut app);
+
+    let color = |entity| app.world.get::<Sprite>(entity).unwrap().color;
+    let tints = app.world.resource::<BackTints>();
+    assert_eq!(color(spade), tints.deck(0));
+    assert_eq!(color(heart), tints.deck(0));
+    assert_eq!(color(second_spade), tints.deck(1));
+    assert_ne!(tints.deck(0), tints.deck(1));
+    assert_eq!(color(face_up), Color::WHITE);
+}
+
+#[test]
+fn rotated_card_is_hit_at_its_own_corners_only() {
+    let rect = Rect::from_center_size(vec2(200.0, -100.0), vec2(100.0, 140.0));
+    let rotation = Quat::from_rotation_z(30f32.to_radians());
+
+    for corner in [
+        vec2(50.0, 70.0),
+        vec2(-50.0, 70.0),
+        vec2(50.0, -70.0),
+        vec2(-50.0, -70.0),
+    ] {
+        let turned = (rotation * corner.extend(0.0)).truncate();
+        assert!(hit_test(rect, rotation, rect.center() + turned * 0.98));
+        assert!(!hit_test(rect, rotation, rect.center() + turned * 1.02));
+        // The unrotated corner sticks out past the turned card.
+        assert!(!hit_test(rect, rotation, rect.center() + corner * 0.98));
+        assert!(hit_test(
+            rect,
+            Quat::IDENTITY,
+            rect.center() + corner * 0.98
+        ));
+    }
+}
+
+#[test]
+fn a_pile_shrunk_to_one_card_collapses_into_a_loose_card() {
+    for enabled in [true, false] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(AutoCollapseSingles(enabled))
+            .add_systems(Update, collapse_single_piles.after(index_piles));
+
+        let regions = GridRegions::split_board();
+        let pos = tableau_pile_pos(&regions, 1);
+        let pile = spawn_fan(
+            &mut app,
+            pos,
+            &[
+                card(Rank::Nine, Suit::Clubs),
+                card(Rank::Eight, Suit::Hearts),
+            ],
+        );
+        let single = spawn_at(
+            &mut app,
+            tableau_pile_pos(&regions, 2),
+            card(Rank::Two, Suit::Spades),
+            Face::Up,
+        );
+        step_frames(&mut app, SETTLE_FRAMES);
+        // A pile that only ever held one card is left alone.
+        assert!(pile_of(&app, single).is_some());
+
+        app.world.despawn(pile[1]);
+        step_frames(&mut app, 2);
+
+        let bottom = pile[0];
+        assert_eq!(pile_of(&app, bottom).is_none(), enabled);
+        assert_eq!(app.world.get::<LooseCell>(bottom).is_some(), enabled);
+        assert!(pile_of(&app, single).is_some());
+    }
+}
+
+/// Three loose cards overlapping left to right, each on top of the last, and
+/// the systems that select them.
+fn overlapping_cards_app() -> (App, [Entity; 3]) {
+    let mut app = board_app();
+    app.add_systems(
+        Update,
+        (update_cursor, update_bounds, select_card, box_select).chain(),
+    );
+    let cards = [Rank::Ace, Rank::Two, Rank::Three].map(|rank| card(rank, Suit::Hearts));
+    let entities = [0, 1, 2].map(|i| {
+        let entity = spawn_at(&mut app, vec2(40.0 * i as f32, 0.0), cards[i], Face::Up);
+        app.world
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .z = i as f32;
+        entity
+    });
+    step(&mut app);
+    (app, entities)
+}
+
+#[test]
+fn selection_modifiers_combine_clicks_and_bands_with_the_carried_cards() {
+    enum Gesture {
+        /// Click where all three cards overlap, so the top one is hit.
+        Click,
+        /// Drag a band from empty space to `to`.
+        Band { to: Vec2 },
+    }
+    use Gesture::*;
+    /// Modifier held, cards carried beforehand, the gesture, and the cards
+    /// carried after.
+    type Case = (Option<KeyCode>, &'static [usize], Gesture, &'static [usize]);
+    let everything = vec2(0.0, 0.0);
+    let right_two = vec2(60.0, 0.0);
+
+    let cases: [Case; 9] = [
+        (None, &[], Click, &[2]),
+        (Some(KeyCode::ShiftLeft), &[0], Click, &[0, 2]),
+        (Some(KeyCode::ShiftLeft), &[2], Click, &[2]),
+        (Some(KeyCode::ControlLeft), &[0], Click, &[0, 2]),
+        (Some(KeyCode::ControlLeft), &[0, 2], Click, &[0]),
+        (None, &[], Band { to: right_two }, &[1, 2]),
+        (
+            Some(KeyCode::ShiftLeft),
+            &[0],
+            Band { to: right_two },
+            &[0, 1, 2],
+        ),
+        (
+            Some(KeyCode::ControlLeft),
+            &[0, 1],
+            Band { to: everything },
+            &[2],
+        ),
+        (
+            Some(KeyCode::ControlLeft),
+            &[1],
+            Band { to: right_two },
+            &[2],
+        ),
+    ];
+
+    for (case, (modifier, carried, gesture, expected)) in cases.into_iter().enumerate() {
+        let (mut app, cards) = overlapping_cards_app();
+        for &i in carried {
+            app.world.entity_mut(cards[i]).insert(Selected);
+        }
+        if let Some(key) = modifier {
+            press_key(&mut app, key);
+        }
+
+        match gesture {
+            Click => {
+                move_pointer(&mut app, vec2(45.0, 0.0));
+                press(&mut app, MouseButton::Left);
+                step(&mut app);
+            }
+            Band { to } => {
+                move_pointer(&mut app, vec2(200.0, 200.0));
+                press(&mut app, MouseButton::Left);
+                step(&mut app);
+                move_pointer(&mut app, to);
+                release(&mut app, MouseButton::Left);
+                step(&mut app);
+            }
+        }
+        step(&mut app);
+
+        let selected = (0..3)
+            .filter(|&i| app.world.get::<Selected>(cards[i]).is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(selected, expected, "case {case}");
+    }
+}
+
+/// Spawns `count` face-down cards stacked in the pile at `pos`, bottom first.
+fn spawn_face_down_pile(app: &mut App, pos: Vec2, count: usize) -> Vec<Entity> {
+    (0..count)
+        .map(|slot| {
+            let entity = spawn_at(app, pos, card(Rank::ALL[slot], Suit::Clubs), Face::Down);
+            app.world.entity_mut(entity).insert(FanSlot(slot, count));
+            entity
+        })
+        .collect()
+}
+
+fn face_of(app: &App, entity: Entity) -> Face {
+    *app.world.get::<Face>(entity).unwrap()
+}
+
+#[test]
+fn taking_the_top_card_flips_the_one_beneath_where_the_zone_says_so() {
+    for on_expose in [true, false] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.add_systems(Update, (auto_flip.after(index_piles), flip_animation));
+        app.world.resource_mut::<AutoFlipOnDrop>().0.insert(
+            ZoneKind::Tableau,
+            // Cards landing as they spawn count as drops.
+            AutoFlip {
+                on_drop: false,
+                on_expose,
+            },
+        );
+
+        let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+        let pile = spawn_face_down_pile(&mut app, pos, 3);
+        step_frames(&mut app, SETTLE_FRAMES);
+        assert_eq!(pile_at(&app, pos), pile);
+        assert!(pile
+            .iter()
+            .all(|entity| face_of(&app, *entity) == Face::Down));
+
+        app.world.despawn(pile[2]);
+        step_frames(&mut app, SETTLE_FRAMES);
+
+        let expected = if on_expose { Face::Up } else { Face::Down };
+        assert_eq!(face_of(&app, pile[1]), expected);
+        assert_eq!(face_of(&app, pile[0]), Face::Down);
+    }
+}
+
+#[test]
+fn emptying_a_pile_flips_nothing_and_a_refill_is_not_an_expose() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_systems(Update, (auto_flip.after(index_piles), flip_animation));
+    app.world.resource_mut::<AutoFlipOnDrop>().0.insert(
+        ZoneKind::Tableau,
+        AutoFlip {
+            on_drop: false,
+            on_expose: true,
+        },
+    );
+
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+    let pile = spawn_face_down_pile(&mut app, pos, 3);
+    step_frames(&mut app, SETTLE_FRAMES);
+
+    // Two cards off at once still uncover the bottom one.
+    app.world.despawn(pile[2]);
+    app.world.despawn(pile[1]);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(face_of(&app, pile[0]), Face::Up);
+
+    app.world.despawn(pile[0]);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert!(pile_at(&app, pos).is_empty());
+
+    let refill = spawn_at(&mut app, pos, card(Rank::King, Suit::Hearts), Face::Down);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(pile_at(&app, pos), [refill]);
+    assert_eq!(face_of(&app, refill), Face::Down);
+}
+
+#[test]
+fn carried_cards_keep_their_fan_slots_from_frame_to_frame() {
+    let mut app = board_app();
+    app.add_systems(Update, (update_cursor, drag_selected).chain());
+
+    // Picked up in the opposite order to how they were spawned.
+    let cards = [Rank::Ace, Rank::Two, Rank::Three, Rank::Four].map(|rank| {
+        let entity = spawn_at(&mut app, Vec2::ZERO, card(rank, Suit::Spades), Face::Up);
+        app.world.entity_mut(entity).remove::<Dragging>();
+        entity
+    });
+    for (order, entity) in cards.iter().rev().enumerate() {
+        app.world
+            .entity_mut(*entity)
+            .insert((Selected, DragOrder(order as u32)));
+    }
+
+    let slot_of = |app: &App, entity: Entity| {
+        // A carried card's z is its place in the fan, counting from 1.
+        app.world.get::<Transform>(entity).unwrap().translation.z as usize - 1
+    };
+    for frame in 0..SETTLE_FRAMES {
+        move_pointer(&mut app, vec2(frame as f32 * 5.0, 0.0));
+        step(&mut app);
+        for (i, entity) in cards.iter().enumerate() {
+            assert_eq!(slot_of(&app, *entity), cards.len() - 1 - i, "frame {frame}");
+        }
+    }
+}
+
+#[test]
+fn box_selecting_past_the_cap_carries_only_the_front_most_cards() {
+    let mut app = board_app();
+    app.insert_resource(MaxSelection(10)).add_systems(
+        Update,
+        (update_cursor, update_bounds, select_card, box_select).chain(),
+    );
+    let cards = (0..50)
+        .map(|i| {
+            let rank = Rank::ALL[i % Rank::ALL.len()];
+            let entity = spawn_at(
+                &mut app,
+                vec2(i as f32 * 2.0, 0.0),
+                card(rank, Suit::Clubs),
+                Face::Up,
+            );
+            app.world
+                .get_mut::<Transform>(entity)
+                .unwrap()
+                .translation
+                .z = i as f32;
+            entity
+        })
+        .collect::<Vec<_>>();
+    step(&mut app);
+
+    move_pointer(&mut app, vec2(300.0, 200.0));
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    move_pointer(&mut app, vec2(-100.0, -200.0));
+    release(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert_eq!(toasts(&app), [selection_capped_toast(&MaxSelection(10)).0]);
+
+    let selected = cards
+        .iter()
+        .map(|entity| app.world.get::<Selected>(*entity).is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(selected.iter().filter(|selected| **selected).count(), 10);
+    assert!(selected[40..].iter().all(|selected| *selected));
+}
+
+#[test]
+fn a_shuffle_pressed_mid_animation_starts_once_it_finishes() {
+    let mut app = board_app();
+    app.add_plugins(InputBufferPlugin)
+        .add_systems(Update, reshuffle.after(BufferedInput));
+    app.world.resource_mut::<ShuffleAnimation>().0 =
+        Some(Timer::from_seconds(SHUFFLE_DURATION, TimerMode::Once));
+    let reshuffles = |app: &App| {
+        app.world
+            .resource::<Events<Reshuffled>>()
+            .iter_current_update_events()
+            .count()
+    };
+
+    press_key(&mut app, KeyCode::R);
+    step(&mut app);
+    assert_eq!(reshuffles(&app), 0);
+    step_frames(&mut app, 3);
+    assert_eq!(reshuffles(&app), 0);
+
+    app.world.resource_mut::<ShuffleAnimation>().0 = None;
+    step(&mut app);
+    assert_eq!(reshuffles(&app), 1);
+    // The press was used up, so it isn't replayed.
+    step(&mut app);
+    assert_eq!(reshuffles(&app), 0);
+
+    // A press left waiting longer than the window is dropped.
+    app.world.resource_mut::<ShuffleAnimation>().0 =
+        Some(Timer::from_seconds(SHUFFLE_DURATION, TimerMode::Once));
+    press_key(&mut app, KeyCode::R);
+    step(&mut app);
+    let window = app.world.resource::<InputBuffer>().window;
+    step_frames(&mut app, (window / FRAME.as_secs_f32()) as usize + 1);
+    app.world.resource_mut::<ShuffleAnimation>().0 = None;
+    step(&mut app);
+    assert_eq!(reshuffles(&app), 0);
+}
+
+#[test]
+fn f_over_empty_space_is_used_up_but_waits_for_a_turning_card() {
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+    let mut app = board_app();
+    app.add_plugins(InputBufferPlugin).add_systems(
+        Update,
+        (update_cursor, update_bounds, hover_cards, flip_hovered)
+            .chain()
+            .after(BufferedInput),
+    );
+    let entity = spawn_at(&mut app, pos, card(Rank::Ace, Suit::Spades), Face::Up);
+    move_pointer(&mut app, pos + vec2(400.0, 0.0));
+    step_frames(&mut app, SETTLE_FRAMES);
+
+    // Pressed with nothing hovered, so reaching a card straight after is no flip.
+    press_key(&mut app, KeyCode::F);
+    step(&mut app);
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::F);
+    move_pointer(&mut app, pos);
+    step_frames(&mut app, 3);
+    assert!(app.world.get::<Hovered>(entity).is_some());
+    assert!(app.world.get::<FlipAnim>(entity).is_none());
+
+    // Pressed while the hovered card is mid-turn, it turns back once it lands.
+    app.world.entity_mut(entity).insert(FlipAnim {
+        t: 0.5,
+        to: Face::Down,
+    });
+    press_key(&mut app, KeyCode::F);
+    step(&mut app);
+    assert_eq!(app.world.get::<FlipAnim>(entity).unwrap().t, 0.5);
+    app.world
+        .entity_mut(entity)
+        .remove::<FlipAnim>()
+        .insert(Face::Down);
+    step(&mut app);
+    assert_eq!(app.world.get::<FlipAnim>(entity).unwrap().to, Face::Up);
+}
+
+#[test]
+fn heavier_cards_trail_further_behind_the_cursor() {
+    let trail = |weight: Option<f32>| {
+        let mut app = board_app();
+        app.add_systems(Update, (update_cursor, drag_selected).chain());
+        let entity = spawn_at(
+            &mut app,
+            Vec2::ZERO,
+            card(Rank::Ace, Suit::Spades),
+            Face::Up,
+        );
+        app.world
+            .entity_mut(entity)
+            .remove::<Dragging>()
+            .insert((Selected, DragOrder(0)));
+        if let Some(weight) = weight {
+            app.world.entity_mut(entity).insert(Weight(weight));
+        }
+
+        (0..20)
+            .map(|frame| {
+                move_pointer(&mut app, vec2(frame as f32 * 20.0, 0.0));
+                step(&mut app);
+                position(&app, entity).x
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let light = trail(None);
+    let heavy = trail(Some(2.0));
+    // Extreme weights are clamped rather than freezing the card.
+    let extreme = trail(Some(1000.0));
+    // The first update has no elapsed time, so nothing has moved yet.
+    for frame in 2..20 {
+        assert!(heavy[frame] < light[frame], "frame {frame}");
+        assert!(extreme[frame] > 0.0, "frame {frame}");
+    }
+}
+
+#[test]
+fn spawned_cards_are_drawn_face_down_from_the_deck() {
+    let mut app = board_app();
+    app.insert_resource(SpawnKey(Some(KeyCode::N)))
+        .insert_resource(Deck::from_notation("AS KH").unwrap())
+        .add_systems(Update, create_card);
+
+    let spawn = |app: &mut App| {
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::N);
+        press_key(app, KeyCode::N);
+        step(app);
+    };
+
+    spawn(&mut app);
+    let spawned = app
+        .world
+        .query::<(&CardData, &Face)>()
+        .iter(&app.world)
+        .map(|(card, face)| (*card, *face))
+        .collect::<Vec<_>>();
+    assert_eq!(spawned, [(card(Rank::King, Suit::Hearts), Face::Down)]);
+    assert_eq!(app.world.resource::<Deck>().len(), 1);
+
+    spawn(&mut app);
+    assert_eq!(card_count(&mut app), 2);
+    spawn(&mut app);
+    assert_eq!(card_count(&mut app), 2);
+    assert_eq!(toasts(&app), ["The deck is empty"]);
+}
+
+#[test]
+fn spawning_twice_gives_the_same_back_unless_cycling() {
+    for cycle in [false, true] {
+        let mut app = board_app();
+        app.insert_resource(SpawnKey(Some(KeyCode::N)))
+            .insert_resource(CycleOnSpawn(cycle))
+            .add_systems(Update, create_card);
+
+        for _ in 0..2 {
+            app.world
+                .resource_mut::<Input<KeyCode>>()
+                .release(KeyCode::N);
+            press_key(&mut app, KeyCode::N);
+            step(&mut app);
+        }
+
+        let backs = app
+            .world
+            .query_filtered::<&Handle<Image>, With<Card>>()
+            .iter(&app.world)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(backs.len(), 2);
+        assert_eq!(backs[0] == backs[1], !cycle, "cycle {cycle}");
+        let assets = app.world.resource::<CardAssets>();
+        assert!(backs.contains(&assets.back(BackTheme::Blue)));
+    }
+}
+
+#[test]
+fn a_recalled_discard_comes_back_as_the_same_card() {
+    let mut app = board_app();
+    app.add_systems(Update, recall_discard);
+    let pos = vec2(100.0, 50.0);
+    app.world.resource_mut::<LastDiscard>().0 = Some(DiscardedCard {
+        card: card(Rank::Seven, Suit::Diamonds),
+        face: Face::Down,
+        position: pos,
+        tags: Tags::default(),
+    });
+    let spawned = |app: &App| app.world.resource::<Events<CardSpawned>>().len();
+
+    // With the board full it stays discarded.
+    app.insert_resource(MaxCards(0));
+    press_key(&mut app, KeyCode::U);
+    step(&mut app);
+    assert_eq!(toasts(&app), ["Card limit of 0 reached"]);
+    assert_eq!(card_count(&mut app), 0);
+    assert!(app.world.resource::<LastDiscard>().0.is_some());
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::U);
+
+    app.insert_resource(MaxCards(1));
+    press_key(&mut app, KeyCode::U);
+    step(&mut app);
+    assert_eq!(spawned(&app), 1);
+    let (entity, data, face) = app
+        .world
+        .query::<(Entity, &CardData, &Face)>()
+        .single(&app.world);
+    assert_eq!(*data, card(Rank::Seven, Suit::Diamonds));
+    assert_eq!(*face, Face::Down);
+    assert_eq!(position(&app, entity), pos);
+    assert!(app.world.resource::<LastDiscard>().0.is_none());
+}
+
+#[test]
+fn a_discarded_card_fades_untouchable_for_the_delay_then_goes() {
+    let mut app = board_app();
+    app.add_plugins(CardIdPlugin)
+        .init_resource::<Inspected>()
+        .insert_resource(DiscardDelay(0.5))
+        .insert_resource(DiscardZone(Rect::from_center_size(
+            vec2(400.0, -200.0),
+            DISCARD_ZONE_SIZE,
+        )))
+        .add_systems(
+            Update,
+            (
+                update_cursor,
+                update_bounds,
+                select_card,
+                discard_dropped,
+                fade_disappearing,
+            )
+                .chain(),
+        )
+        .add_systems(PostUpdate, despawn_cards);
+    let pos = vec2(400.0, -200.0);
+    let entity = spawn_at(&mut app, pos, card(Rank::Nine, Suit::Spades), Face::Up);
+    app.world.entity_mut(entity).remove::<Dragging>().insert((
+        Selected,
+        DragOrigin(Vec2::ZERO),
+        DragVelocity(Vec2::ZERO),
+        DragOrder(0),
+    ));
+    move_pointer(&mut app, pos);
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+
+    release(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Disappearing>(entity).is_some());
+
+    // Halfway through the delay the card is still there, fading, and a click
+    // on it picks nothing up.
+    step_frames(&mut app, 15);
+    let alpha = app.world.get::<Sprite>(entity).unwrap().color.a();
+    assert!(0.0 < alpha && alpha < 1.0, "{alpha}");
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Selected>(entity).is_none());
+
+    step_frames(&mut app, 20);
+    assert!(app.world.get_entity(entity).is_none());
+}
+
+#[test]
+fn tall_fans_squash_to_the_max_extent_and_short_ones_keep_full_spacing() {
+    let extent = 120.0;
+
+    let tall = (0..20)
+        .map(|slot| pile_offset_for(slot, 20, extent))
+        .collect::<Vec<_>>();
+    assert!(tall
+        .iter()
+        .all(|offset| offset.x <= extent && offset.y <= extent));
+    assert_eq!(tall[19], Vec2::splat(extent));
+    let gaps = tall.windows(2).map(|pair| pair[1] - pair[0]);
+    assert!(gaps.into_iter().all(|gap| gap.x > 0.0 && gap.x < FAN_STEP));
+
+    let short = (0..3)
+        .map(|slot| pile_offset_for(slot, 3, extent))
+        .collect::<Vec<_>>();
+    assert_eq!(short, [0.0, FAN_STEP, 2.0 * FAN_STEP].map(Vec2::splat));
+}
+
+/// An app that deals from the stock onto the waste, with `deck` in the stock.
+fn stock_app(per_click: usize, deck: &str) -> App {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_plugins(CardIdPlugin)
+        .init_resource::<Inspected>()
+        .insert_resource(StockDeal { per_click })
+        .insert_resource(Deck::from_notation(deck).unwrap())
+        .add_systems(
+            Update,
+            (
+                click_stock.after(update_cursor).before(select_card),
+                flip_animation,
+            ),
+        )
+        .add_systems(PostUpdate, despawn_cards);
+    app
+}
+
+fn click_stock_once(app: &mut App) {
+    move_pointer(app, STOCK_POS);
+    press(app, MouseButton::Left);
+    step(app);
+    release(app, MouseButton::Left);
+    step_frames(app, SETTLE_FRAMES);
+}
+
+fn waste(app: &App) -> Vec<(CardData, Face)> {
+    let pos = waste_pos(app.world.resource::<GridRegions>());
+    pile_at(app, pos)
+        .into_iter()
+        .map(|entity| {
+            (
+                *app.world.get::<CardData>(entity).unwrap(),
+                face_of(app, entity),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn one_card_mode_turns_over_a_card_per_click() {
+    let mut app = stock_app(1, "AS 2S 3S");
+
+    click_stock_once(&mut app);
+    assert_eq!(waste(&app), [(card(Rank::Three, Suit::Spades), Face::Up)]);
+    click_stock_once(&mut app);
+    assert_eq!(app.world.resource::<Deck>().len(), 1);
+    assert_eq!(waste(&app).len(), 2);
+    assert_eq!(
+        waste(&app).last(),
+        Some(&(card(Rank::Two, Suit::Spades), Face::Up))
+    );
+}
+
+#[test]
+fn three_card_mode_turns_over_three_with_the_last_on_top() {
+    let mut app = stock_app(3, "AS 2S 3S 4S");
+
+    click_stock_once(&mut app);
+    let dealt = waste(&app);
+    let cards = dealt.iter().map(|(card, _)| *card).collect::<Vec<_>>();
+    assert_eq!(
+        cards,
+        [Rank::Four, Rank::Three, Rank::Two].map(|rank| card(rank, Suit::Spades))
+    );
+    assert!(dealt.iter().all(|(_, face)| *face == Face::Up), "{dealt:?}");
+
+    // Only what is left is dealt.
+    click_stock_once(&mut app);
+    assert_eq!(waste(&app).len(), 4);
+    assert!(app.world.resource::<Deck>().is_empty());
+}
+
+#[test]
+fn clicking_the_empty_stock_turns_the_waste_back_over() {
+    let mut app = stock_app(1, "AS 2S 3S");
+    for _ in 0..3 {
+        click_stock_once(&mut app);
+    }
+    assert!(app.world.resource::<Deck>().is_empty());
+
+    click_stock_once(&mut app);
+    assert!(waste(&app).is_empty());
+    assert_eq!(card_count(&mut app), 0);
+    let deck = app.world.resource::<Deck>();
+    assert!(deck
+        .iter()
+        .eq(Deck::from_notation("AS 2S 3S").unwrap().iter()));
+
+    click_stock_once(&mut app);
+    assert_eq!(waste(&app), [(card(Rank::Three, Suit::Spades), Face::Up)]);
+}
+
+#[test]
+fn high_contrast_swaps_card_textures_and_back_again() {
+    let mut app = board_app();
+    app.add_systems(
+        Update,
+        (
+            toggle_high_contrast,
+            flip_animation,
+            apply_contrast_textures,
+        )
+            .chain(),
+    );
+    let up = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Ace, Suit::Hearts),
+        Face::Up,
+    );
+    let down = spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Two, Suit::Hearts),
+        Face::Down,
+    );
+    step(&mut app);
+
+    let texture = |app: &App, entity| app.world.get::<Handle<Image>>(entity).unwrap().clone();
+    let assets = app.world.resource::<CardAssets>();
+    let (front, back) = (assets.front.clone(), assets.back(BackTheme::default()));
+    let (contrast_front, contrast_back) =
+        (assets.contrast_front.clone(), assets.contrast_back.clone());
+    assert_eq!(texture(&app, up), front);
+
+    press_key(&mut app, KeyCode::X);
+    step(&mut app);
+    assert_eq!(texture(&app, up), contrast_front);
+    assert_eq!(texture(&app, down), contrast_back);
+
+    // A card flipped while the mode is on lands on the contrast texture too.
+    app.world.entity_mut(down).insert(FlipAnim {
+        t: 0.0,
+        to: Face::Up,
+    });
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(texture(&app, down), contrast_front);
+    app.world.entity_mut(down).insert(FlipAnim {
+        t: 0.0,
+        to: Face::Down,
+    });
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(texture(&app, down), contrast_back);
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::X);
+    press_key(&mut app, KeyCode::X);
+    step(&mut app);
+    assert_eq!(texture(&app, up), front);
+    assert_eq!(texture(&app, down), back);
+}
+
+/// A board app that saves to and loads from its own file in the temp dir.
+fn save_app(name: &str) -> App {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    let path = std::env::temp_dir().join(format!(
+        "asymetric_cards-{name}-{}.save",
+        std::process::id()
+    ));
+    app.add_plugins(CardIdPlugin)
+        .init_resource::<Inspected>()
+        .insert_resource(SaveConfig { path })
+        .add_event::<ConsoleInput>()
+        .add_event::<ConsoleOutput>()
+        .add_systems(Update, (save_board, load_board).before(update_cursor))
+        .add_systems(PostUpdate, despawn_cards);
+    app
+}
+
+fn console(app: &mut App, command: ConsoleCommand) {
+    app.world.send_event(ConsoleInput(command));
+    step(app);
+}
+
+/// Every card's `CardId`, with what it is and where it rests.
+fn cards_by_id(app: &mut App) -> Vec<(CardId, CardData, Vec2)> {
+    let mut cards = app
+        .world
+        .query::<(&CardId, &CardData, &Transform)>()
+        .iter(&app.world)
+        .map(|(id, card, transform)| (*id, *card, transform.translation.xy()))
+        .collect::<Vec<_>>();
+    cards.sort_by_key(|(id, ..)| *id);
+    cards
+}
+
+#[test]
+fn card_ids_survive_a_save_and_load() {
+    let mut app = save_app("ids");
+    let regions = GridRegions::split_board();
+    let pos = tableau_pile_pos(&regions, 1);
+    let pile = spawn_fan(
+        &mut app,
+        pos,
+        &[
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Eight, Suit::Hearts),
+        ],
+    );
+    spawn_at(
+        &mut app,
+        tableau_pile_pos(&regions, 6),
+        card(Rank::Queen, Suit::Spades),
+        Face::Up,
+    );
+    step_frames(&mut app, SETTLE_FRAMES);
+    let before = cards_by_id(&mut app);
+    assert_eq!(before.len(), 3);
+
+    console(&mut app, ConsoleCommand::Save);
+    // Spawning more before loading moves the next id on.
+    spawn_at(
+        &mut app,
+        Vec2::ZERO,
+        card(Rank::Two, Suit::Hearts),
+        Face::Up,
+    );
+    step(&mut app);
+    console(&mut app, ConsoleCommand::Load);
+    step_frames(&mut app, SETTLE_FRAMES);
+    std::fs::remove_file(&app.world.resource::<SaveConfig>().path).unwrap();
+
+    let after = cards_by_id(&mut app);
+    assert_eq!(after.len(), before.len());
+    for ((id, card, pos), (loaded_id, loaded_card, loaded_pos)) in before.iter().zip(&after) {
+        assert_eq!((id, card), (loaded_id, loaded_card));
+        assert!(pos.distance(*loaded_pos) < 1.0, "{pos} != {loaded_pos}");
+
+        let entity = app.world.resource::<CardIds>().get(*id).unwrap();
+        assert_eq!(app.world.get::<CardId>(entity), Some(id));
+        assert!(!pile.contains(&entity));
+    }
+    // The pile restacks in the same order.
+    let stacked = pile_at(&app, pos)
+        .iter()
+        .map(|entity| *app.world.get::<CardData>(*entity).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stacked,
+        [
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Eight, Suit::Hearts)
+        ]
+    );
+    assert_eq!(app.world.resource::<card_id::NextCardId>().0, 4);
+}
+
+#[test]
+fn tags_and_faces_survive_a_save_and_load() {
+    let mut app = save_app("tags");
+    let regions = GridRegions::split_board();
+    let wild = spawn_at(
+        &mut app,
+        tableau_pile_pos(&regions, 0),
+        card(Rank::Jack, Suit::Diamonds),
+        Face::Down,
+    );
+    spawn_at(
+        &mut app,
+        tableau_pile_pos(&regions, 2),
+        card(Rank::Three, Suit::Clubs),
+        Face::Up,
+    );
+    step_frames(&mut app, SETTLE_FRAMES);
+    let mut tags = app.world.get_mut::<Tags>(wild).unwrap();
+    assert_eq!(tags.add("wild"), Ok(true));
+    assert_eq!(tags.add("bonus"), Ok(true));
+    assert_eq!(tags.add("wild"), Ok(false));
+    // Neither could be written back as one word.
+    assert!(tags.add("two words").is_err());
+    assert!(tags.add("").is_err());
+    let wild_id = *app.world.get::<CardId>(wild).unwrap();
+
+    console(&mut app, ConsoleCommand::Save);
+    console(&mut app, ConsoleCommand::Load);
+    step_frames(&mut app, SETTLE_FRAMES);
+    std::fs::remove_file(&app.world.resource::<SaveConfig>().path).unwrap();
+
+    let loaded = app.world.resource::<CardIds>().get(wild_id).unwrap();
+    assert_ne!(loaded, wild);
+    assert_eq!(cards_with_tag(&app.world, "wild"), [loaded]);
+    assert_eq!(cards_with_tag(&app.world, "bonus"), [loaded]);
+    assert_eq!(app.world.get::<Tags>(loaded).unwrap().len(), 2);
+    assert_eq!(app.world.get::<Face>(loaded), Some(&Face::Down));
+}
+
+#[test]
+fn carried_cards_all_close_in_on_the_cursor_without_overshooting() {
+    let mut app = board_app();
+    app.add_systems(Update, (update_cursor, drag_selected).chain());
+
+    let cards = [Rank::Ace, Rank::Two, Rank::Three, Rank::Four].map(|rank| {
+        let entity = spawn_at(
+            &mut app,
+            vec2(-300.0, 0.0),
+            card(rank, Suit::Spades),
+            Face::Up,
+        );
+        app.world.entity_mut(entity).remove::<Dragging>();
+        entity
+    });
+    for (order, entity) in cards.iter().enumerate() {
+        app.world
+            .entity_mut(*entity)
+            .insert((Selected, DragOrder(order as u32)));
+    }
+
+    let cursor = vec2(200.0, 50.0);
+    let extent = app.world.resource::<MaxPileExtent>().0;
+    let targets = (0..cards.len())
+        .map(|slot| cursor + pile_offset_for(slot, cards.len(), extent))
+        .collect::<Vec<_>>();
+    let gaps = |app: &App| {
+        cards
+            .iter()
+            .zip(&targets)
+            .map(|(entity, target)| position(app, *entity).distance(*target))
+            .collect::<Vec<_>>()
+    };
+
+    move_pointer(&mut app, cursor);
+    let mut last = gaps(&app);
+    for frame in 0..120 {
+        step(&mut app);
+        let now = gaps(&app);
+        for (slot, (now, last)) in now.iter().zip(&last).enumerate() {
+            // Steady closing in, bar a few pixels of integration error at the end.
+            assert!(
+                *now <= last.max(3.0),
+                "slot {slot} backed off on frame {frame}: {last} -> {now}"
+            );
+        }
+        if frame == 5 {
+            // Further along the fan trails a little further behind.
+            let fraction = |slot: usize| now[slot] / targets[slot].distance(vec2(-300.0, 0.0));
+            for slot in 1..cards.len() {
+                assert!(
+                    fraction(slot) > fraction(slot - 1),
+                    "slot {slot} on frame {frame}"
+                );
+            }
+        }
+        last = now;
+    }
+    for (slot, gap) in last.iter().enumerate() {
+        assert!(*gap < 1.0, "slot {slot} is still {gap} from the cursor");
+    }
+}
+
+fn right_click_app() -> App {
+    let mut app = board_app();
+    app.init_resource::<Inspected>().add_systems(
+        Update,
+        (
+            update_cursor,
+            update_bounds,
+            (
+                resolve_right_click,
+                create_card,
+                cancel_drag,
+                open_context_menu,
+            )
+                .chain(),
+        )
+            .chain(),
+    );
+    app
+}
+
+fn right_click(app: &mut App, at: Vec2) {
+    move_pointer(app, at);
+    press(app, MouseButton::Right);
+    step(app);
+    release(app, MouseButton::Right);
+    step(app);
+}
+
+#[test]
+fn right_clicking_a_card_inspects_it_and_empty_space_spawns_one() {
+    let mut app = right_click_app();
+    let regions = GridRegions::split_board();
+    let pos = tableau_pile_pos(&regions, 0);
+    let clicked = spawn_at(&mut app, pos, card(Rank::Four, Suit::Hearts), Face::Up);
+    step(&mut app);
+
+    right_click(&mut app, pos);
+    assert_eq!(app.world.resource::<Inspected>().0, Some(clicked));
+    assert_eq!(card_count(&mut app), 1);
+
+    right_click(&mut app, tableau_pile_pos(&regions, 6));
+    assert_eq!(card_count(&mut app), 2);
+    assert_eq!(app.world.resource::<Inspected>().0, Some(clicked));
+}
+
+#[test]
+fn right_clicking_while_carrying_only_cancels_the_drag() {
+    let mut app = right_click_app();
+    let regions = GridRegions::split_board();
+    let origin = tableau_pile_pos(&regions, 0);
+    let under = tableau_pile_pos(&regions, 2);
+    spawn_at(&mut app, under, card(Rank::Four, Suit::Hearts), Face::Up);
+    let carried = spawn_at(&mut app, under, card(Rank::Five, Suit::Clubs), Face::Up);
+    app.world
+        .entity_mut(carried)
+        .insert((Selected, DragOrigin(origin)));
+    step(&mut app);
+
+    // Over a card, and carrying one, so the drag wins.
+    right_click(&mut app, under);
+    assert!(app.world.get::<Selected>(carried).is_none());
+    assert_eq!(
+        app.world
+            .get::<Dragging>(carried)
+            .map(|dragging| dragging.0),
+        Some(origin)
+    );
+    assert_eq!(app.world.resource::<Inspected>().0, None);
+    assert_eq!(card_count(&mut app), 2);
+}
+
+#[test]
+fn querying_a_pile_answers_with_its_card_ids_bottom_first() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_plugins(CardIdPlugin)
+        .add_systems(Update, answer_pile_queries.after(index_piles));
+
+    let regions = GridRegions::split_board();
+    let pos = tableau_pile_pos(&regions, 1);
+    let ranks = [Rank::King, Rank::Queen, Rank::Jack];
+    let pile = spawn_fan(&mut app, pos, &ranks.map(|rank| card(rank, Suit::Spades)));
+    step_frames(&mut app, SETTLE_FRAMES);
+    let ids = pile
+        .iter()
+        .map(|entity| *app.world.get::<CardId>(*entity).unwrap())
+        .collect::<Vec<_>>();
+
+    let empty = tableau_pile_pos(&regions, 6);
+    app.world.send_event(QueryPile(pos + vec2(10.0, -10.0)));
+    app.world.send_event(QueryPile(empty));
+    step(&mut app);
+
+    let answers = app
+        .world
+        .resource::<Events<PileContents>>()
+        .iter_current_update_events()
+        .map(|PileContents { cell, cards }| (*cell, cards.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(answers, [(pos, ids), (empty, Vec::new())]);
+}
+
+/// Carries a fresh ace from `from` and lets go of it at `at`.
+fn drop_at(app: &mut App, from: Vec2, at: Vec2) -> Entity {
+    drop_card_at(app, card(Rank::Ace, Suit::Diamonds), from, at)
+}
+
+fn drop_card_at(app: &mut App, card: CardData, from: Vec2, at: Vec2) -> Entity {
+    let carried = spawn_at(app, from, card, Face::Up);
+    move_pointer(app, from);
+    step_frames(app, SETTLE_FRAMES);
+    press(app, MouseButton::Left);
+    step(app);
+    for i in 1..=10 {
+        move_pointer(app, from.lerp(at, i as f32 / 10.0));
+        step(app);
+    }
+    release(app, MouseButton::Left);
+    step_frames(app, SETTLE_FRAMES);
+    carried
+}
+
+#[test]
+fn drops_join_a_pile_only_within_the_snap_radius() {
+    const RADIUS: f32 = 120.0;
+    let regions = GridRegions::split_board();
+    let pile = tableau_pile_pos(&regions, 1);
+    let from = tableau_pile_pos(&regions, 6);
+
+    for (reach, joins) in [(RADIUS - 10.0, true), (RADIUS + 10.0, false)] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(SnapRadius(RADIUS));
+        let base = spawn_at(&mut app, pile, card(Rank::Three, Suit::Spades), Face::Up);
+
+        // Off to the side, in a cell of its own either way.
+        let at = pile + vec2(reach, 0.0);
+        assert_ne!(align_grid(&regions, at), pile);
+        let carried = drop_at(&mut app, from, at);
+
+        assert_eq!(
+            pile_of(&app, carried) == pile_of(&app, base),
+            joins,
+            "dropped {reach} away"
+        );
+        if !joins {
+            assert_eq!(pile_at(&app, at), [carried]);
+        }
+    }
+}
+
+#[test]
+fn a_card_lifted_off_a_foundation_and_dropped_back_scores_once() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.init_resource::<Scored>()
+        .add_systems(Update, report_score_events.after(finish_drag_selected))
+        .add_systems(Last, record_scores);
+
+    let regions = GridRegions::split_board();
+    let foundation = Foundations::top_right(&regions)[0].center();
+    let from = tableau_pile_pos(&regions, 1);
+    let ace = drop_at(&mut app, from, foundation);
+    assert_eq!(pile_at(&app, foundation), [ace]);
+    assert_eq!(
+        app.world.resource::<Scored>().events,
+        [(ScoreEvent::CardToFoundation, true)]
+    );
+
+    // Up and straight back down again.
+    move_pointer(&mut app, foundation);
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Selected>(ace).is_some());
+    move_pointer(&mut app, foundation + vec2(30.0, -30.0));
+    step_frames(&mut app, 5);
+    release(&mut app, MouseButton::Left);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(pile_at(&app, foundation), [ace]);
+    assert_eq!(app.world.resource::<Scored>().events.len(), 1);
+}
+
+#[test]
+fn changing_the_anchor_moves_the_foundations_and_pile_markers() {
+    let mut app = board_app();
+    app.add_systems(Update, (cycle_grid_anchor, refit_piles_to_grid).chain());
+    let mut queue = CommandQueue::default();
+    let world = &app.world;
+    spawn_pile_slots(
+        world.resource::<GridRegions>(),
+        world.resource::<Foundations>(),
+        &mut Commands::new(&mut queue, world),
+    );
+    queue.apply(&mut app.world);
+    let before = app.world.resource::<Foundations>().to_vec();
+
+    press_key(&mut app, KeyCode::N);
+    step(&mut app);
+    step(&mut app);
+
+    let regions = app.world.resource::<GridRegions>();
+    assert_eq!(regions.fallback.anchor, grid::Anchor::TopLeft);
+    let after = app.world.resource::<Foundations>().to_vec();
+    assert_eq!(after, Foundations::top_right(regions).to_vec());
+    assert_ne!(after, before);
+
+    let mut marked = app
+        .world
+        .query::<(&PileSlot, &Transform)>()
+        .iter(&app.world)
+        .filter(|(slot, _)| slot.kind == ZoneKind::Foundation)
+        .map(|(_, transform)| transform.translation.xy())
+        .collect::<Vec<_>>();
+    marked.sort_by(|a, b| a.x.total_cmp(&b.x));
+    let centres = after.iter().map(Rect::center).collect::<Vec<_>>();
+    assert_eq!(marked, centres);
+    let slots = app.world.query::<&PileSlot>().iter(&app.world).count();
+    assert_eq!(slots, TABLEAU_PILES + after.len());
+}
+
+#[test]
+fn a_cursor_jump_is_smoothed_over_several_frames() {
+    let cursor = |app: &App| app.world.resource::<WordCursor>().0;
+    let from = vec2(-200.0, 0.0);
+    let to = vec2(200.0, 100.0);
+
+    for factor in [0.0, 0.5] {
+        let mut app = board_app();
+        app.insert_resource(CursorSmoothing { factor })
+            .add_systems(Update, update_cursor);
+        move_pointer(&mut app, from);
+        step_frames(&mut app, 30);
+        assert!(cursor(&app).distance(from) < 0.01);
+
+        move_pointer(&mut app, to);
+        step(&mut app);
+        if factor == 0.0 {
+            assert_eq!(cursor(&app), to);
+            continue;
+        }
+
+        // Half the remaining way each frame.
+        let mut gap = from.distance(to);
+        for frame in 0..5 {
+            let now = cursor(&app).distance(to);
+            assert!((now - gap * factor).abs() < 0.01, "frame {frame}: {now}");
+            gap = now;
+            step(&mut app);
+        }
+        step_frames(&mut app, 30);
+        assert!(cursor(&app).distance(to) < 0.01);
+    }
+}
+
+#[test]
+fn an_illegal_drop_goes_back_to_where_it_was_picked_up() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_systems(Update, wiggle.after(finish_drag_selected));
+
+    let regions = GridRegions::split_board();
+    let foundation = Foundations::top_right(&regions)[0].center();
+    let from = tableau_pile_pos(&regions, 1);
+    let two = drop_card_at(&mut app, card(Rank::Two, Suit::Clubs), from, foundation);
+
+    assert!(pile_at(&app, foundation).is_empty());
+    assert_eq!(pile_at(&app, from), [two]);
+    assert!(position(&app, two).distance(from) < 1.0);
+    assert!(app.world.get::<Wiggle>(two).is_none());
+    assert_eq!(
+        app.world.get::<Transform>(two).unwrap().rotation,
+        Quat::IDENTITY
+    );
+}
+
+#[test]
+fn picking_up_a_face_down_card_turns_it_face_up() {
+    for reveal in [true, false] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(RevealOnSelect(reveal)).add_systems(
+            Update,
+            (reveal_on_select, flip_animation)
+                .chain()
+                .after(select_card),
+        );
+
+        let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+        let hidden = spawn_at(&mut app, pos, card(Rank::Six, Suit::Hearts), Face::Down);
+        move_pointer(&mut app, pos);
+        step_frames(&mut app, SETTLE_FRAMES);
+        press(&mut app, MouseButton::Left);
+        step(&mut app);
+        assert!(app.world.get::<Selected>(hidden).is_some());
+
+        // Held the whole time, and it only turns over once.
+        step_frames(&mut app, SETTLE_FRAMES);
+        let expected = if reveal { Face::Up } else { Face::Down };
+        assert_eq!(face_of(&app, hidden), expected);
+        assert!(app.world.get::<FlipAnim>(hidden).is_none());
+    }
+}
+
+#[test]
+fn compacting_tidies_a_pile_into_its_fan_rising_a_step_a_card() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_systems(
+        Update,
+        (compact_piles, settle_compacted, ysort)
+            .chain()
+            .after(index_piles),
+    );
+
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+    let ranks = [Rank::Ten, Rank::Nine, Rank::Eight, Rank::Seven];
+    let pile = spawn_fan(&mut app, pos, &ranks.map(|rank| card(rank, Suit::Diamonds)));
+    step_frames(&mut app, SETTLE_FRAMES);
+    // Knocked out of line, as separate drops leave them.
+    for (i, entity) in pile.iter().enumerate() {
+        let nudge = vec3(i as f32 * 7.0 - 10.0, 12.0 - i as f32 * 5.0, 0.0);
+        app.world.get_mut::<Transform>(*entity).unwrap().translation += nudge;
+    }
+
+    app.world.send_event(CompactPiles);
+    step_frames(&mut app, SETTLE_FRAMES);
+
+    let extent = app.world.resource::<MaxPileExtent>().0;
+    let depths = pile
+        .iter()
+        .enumerate()
+        .map(|(slot, entity)| {
+            let expected = pos + pile_offset_for(slot, pile.len(), extent);
+            let landed = position(&app, *entity);
+            assert!(landed.distance(expected) < 0.01, "{landed} != {expected}");
+            app.world.get::<Transform>(*entity).unwrap().translation.z
+        })
+        .collect::<Vec<_>>();
+    for pair in depths.windows(2) {
+        assert!((pair[1] - pair[0] - PILE_Z_STEP).abs() < 1e-5, "{depths:?}");
+    }
+}
+
+fn touch_app() -> App {
+    let mut app = board_app();
+    add_cards_camera(&mut app, 1280.0, 720.0);
+    app.init_resource::<Touches>()
+        .add_event::<TouchInput>()
+        .add_systems(PreUpdate, touch_screen_input_system)
+        .add_systems(
+            Update,
+            (
+                touch_gesture,
+                update_cursor,
+                update_bounds,
+                drag_selected,
+                finish_drag_selected,
+                align_placed,
+                index_piles,
+                flip_animation,
+            )
+                .chain(),
+        );
+    app
+}
+
+fn touch(app: &mut App, phase: TouchPhase, world: Vec2) {
+    // The default camera centres the world in the 1280x720 window, y up.
+    let position = vec2(world.x + 640.0, 360.0 - world.y);
+    app.world.send_event(TouchInput {
+        phase,
+        position,
+        force: None,
+        id: 0,
+    });
+    step(app);
+}
+
+#[test]
+fn a_short_still_touch_flips_and_a_moving_one_drags_at_once() {
+    let regions = GridRegions::split_board();
+    let pos = tableau_pile_pos(&regions, 1);
+    let to = tableau_pile_pos(&regions, 2);
+    let setup = || {
+        let mut app = touch_app();
+        let entity = spawn_at(&mut app, pos, card(Rank::Ace, Suit::Clubs), Face::Up);
+        step_frames(&mut app, SETTLE_FRAMES);
+        (app, entity)
+    };
+
+    // A tap.
+    let (mut app, tapped) = setup();
+    touch(&mut app, TouchPhase::Started, pos);
+    touch(&mut app, TouchPhase::Ended, pos);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(face_of(&app, tapped), Face::Down);
+    assert_eq!(pile_at(&app, pos), [tapped]);
+
+    // Moving off straight away picks the card up on that very frame.
+    let (mut app, dragged) = setup();
+    touch(&mut app, TouchPhase::Started, pos);
+    touch(&mut app, TouchPhase::Moved, pos + vec2(40.0, 0.0));
+    assert!(app.world.get::<Selected>(dragged).is_some());
+    for i in 1..=10 {
+        touch(&mut app, TouchPhase::Moved, pos.lerp(to, i as f32 / 10.0));
+    }
+    touch(&mut app, TouchPhase::Ended, to);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(face_of(&app, dragged), Face::Up);
+    assert_eq!(pile_at(&app, to), [dragged]);
+
+    // Held still, so it flips on release rather than sticking to the finger.
+    let (mut app, held) = setup();
+    touch(&mut app, TouchPhase::Started, pos);
+    step_frames(&mut app, 30);
+    touch(&mut app, TouchPhase::Ended, pos);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert_eq!(face_of(&app, held), Face::Down);
+    assert!(app.world.get::<Selected>(held).is_none());
+    assert_eq!(pile_at(&app, pos), [held]);
+}
+
+#[test]
+fn the_stock_deals_from_the_deal_source_with_the_deal_facing_up() {
+    let mut app = stock_app(2, "AS 2S 3S 4S 5S");
+    app.insert_resource(DealSource::Bottom)
+        .insert_resource(DealFacing::FaceDown);
+
+    click_stock_once(&mut app);
+    assert_eq!(
+        waste(&app),
+        [Rank::Ace, Rank::Two].map(|rank| (card(rank, Suit::Spades), Face::Down))
+    );
+    assert_eq!(app.world.resource::<Deck>().len(), 3);
+
+    app.insert_resource(DealSource::Top)
+        .insert_resource(DealFacing::FaceUp);
+    click_stock_once(&mut app);
+    let dealt = waste(&app);
+    assert_eq!(dealt.len(), 4);
+    assert_eq!(
+        dealt[2..],
+        [Rank::Five, Rank::Four].map(|rank| (card(rank, Suit::Spades), Face::Up))
+    );
+    assert!(app
+        .world
+        .resource::<Deck>()
+        .iter()
+        .eq([card(Rank::Three, Suit::Spades)].iter()));
+}
+
+#[test]
+fn a_collapsed_waste_is_turned_back_over_too() {
+    let mut app = stock_app(1, "AS 2S");
+    app.insert_resource(AutoCollapseSingles(true))
+        .add_systems(Update, collapse_single_piles.after(index_piles));
+    click_stock_once(&mut app);
+    click_stock_once(&mut app);
+    assert_eq!(waste(&app).len(), 2);
+
+    // Taking the top card off leaves a single, which collapses out of the pile.
+    let regions = GridRegions::split_board();
+    let waste = waste_pos(&regions);
+    let moved = pile_at(&app, waste)[1];
+    let to = tableau_pile_pos(&regions, 1);
+    move_pointer(&mut app, waste);
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    move_pointer(&mut app, to);
+    step_frames(&mut app, 5);
+    release(&mut app, MouseButton::Left);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert!(pile_at(&app, waste).is_empty());
+    assert_eq!(card_count(&mut app), 2);
+
+    click_stock_once(&mut app);
+    assert_eq!(card_count(&mut app), 1);
+    assert!(app.world.get_entity(moved).is_some());
+    assert!(app
+        .world
+        .resource::<Deck>()
+        .iter()
+        .eq([card(Rank::Two, Suit::Spades)].iter()));
+}
+
+#[test]
+fn cards_on_the_background_layer_cant_be_picked_up_or_inspected() {
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.insert_resource(CardLayer(BACKGROUND_LAYER));
+    let inert = spawn_at(&mut app, pos, card(Rank::Nine, Suit::Hearts), Face::Up);
+    move_pointer(&mut app, pos);
+    step_frames(&mut app, SETTLE_FRAMES);
+    assert!(app.world.get::<Inert>(inert).is_some());
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert!(app.world.get::<Selected>(inert).is_none());
+
+    // Right-clicking it is right-clicking empty space.
+    let mut app = right_click_app();
+    app.insert_resource(CardLayer(BACKGROUND_LAYER));
+    spawn_at(&mut app, pos, card(Rank::Nine, Suit::Hearts), Face::Up);
+    step(&mut app);
+    right_click(&mut app, pos);
+    assert_eq!(app.world.resource::<Inspected>().0, None);
+    assert_eq!(card_count(&mut app), 2);
+
+    // Alt + left click on it is a click on empty space, which clears the panel.
+    let mut app = board_app();
+    app.init_resource::<Inspected>()
+        .add_systems(Update, (update_cursor, update_bounds, inspect_card).chain());
+    app.insert_resource(CardLayer(BACKGROUND_LAYER));
+    let inert = spawn_at(&mut app, pos, card(Rank::Nine, Suit::Hearts), Face::Up);
+    move_pointer(&mut app, pos);
+    step_frames(&mut app, SETTLE_FRAMES);
+    app.insert_resource(Inspected(Some(inert)));
+    press_key(&mut app, KeyCode::AltLeft);
+    press(&mut app, MouseButton::Left);
+    step(&mut app);
+    assert_eq!(app.world.resource::<Inspected>().0, None);
+}
+
+#[test]
+fn fan_hover_only_spreads_the_top_peek_count_cards() {
+    let pos = tableau_pile_pos(&GridRegions::split_board(), 1);
+    let hover = pos - vec2(40.0, 60.0);
+
+    for peek in [3, 0] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(PeekCount(peek)).add_systems(
+            Update,
+            (ysort, hover_cards, fan_hover).chain().after(index_piles),
+        );
+        let cards = Rank::ALL[..10].iter().map(|rank| card(*rank, Suit::Clubs));
+        let pile = spawn_fan(&mut app, pos, &cards.collect::<Vec<_>>());
+        step_frames(&mut app, SETTLE_FRAMES);
+        let rest = pile
+            .iter()
+            .map(|entity| position(&app, *entity))
+            .collect::<Vec<_>>();
+
+        // Over the corner only the bottom card shows.
+        move_pointer(&mut app, hover);
+        step_frames(&mut app, 20);
+        assert!(app.world.get::<Hovered>(pile[0]).is_some());
+        let lifted = pile
+            .iter()
+            .zip(&rest)
+            .map(|(entity, rest)| position(&app, *entity).distance(*rest) > 1.0)
+            .collect::<Vec<_>>();
+
+        if peek == 3 {
+            // The top three spread from their own bottom, however low the cursor.
+            assert_eq!(lifted[..7], [false; 7]);
+            assert_eq!(lifted[7..], [true; 3]);
+        } else {
+            assert!(lifted[0] && !lifted[9], "{lifted:?}");
+        }
+    }
+}
+
+#[test]
+fn drops_keep_the_selection_only_when_persisting() {
+    let regions = GridRegions::split_board();
+    let from = tableau_pile_pos(&regions, 0);
+    let at = tableau_pile_pos(&regions, 3);
+
+    for persist in [false, true] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(PersistSelection(persist));
+
+        let dropped = drop_at(&mut app, from, at);
+        assert_eq!(pile_at(&app, at), [dropped], "persist {persist}");
+        assert!(app.world.get::<Selected>(dropped).is_none());
+        assert_eq!(
+            app.world.get::<KeptSelection>(dropped).is_some(),
+            persist,
+            "persist {persist}"
+        );
+    }
+}
+
+#[test]
+fn escape_skips_the_shuffle_before_it_clears_the_kept_selection() {
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.insert_resource(PersistSelection(true))
+        .add_systems(Update, shuffle_animation.before(select_card));
+
+    let regions = GridRegions::split_board();
+    let kept = drop_at(
+        &mut app,
+        tableau_pile_pos(&regions, 0),
+        tableau_pile_pos(&regions, 3),
+    );
+    app.world.resource_mut::<ShuffleAnimation>().0 =
+        Some(Timer::from_seconds(SHUFFLE_DURATION, TimerMode::Once));
+
+    press_key(&mut app, KeyCode::Escape);
+    step(&mut app);
+    assert!(app.world.resource::<ShuffleAnimation>().0.is_none());
+    assert!(app.world.get::<KeptSelection>(kept).is_some());
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::Escape);
+    press_key(&mut app, KeyCode::Escape);
+    step(&mut app);
+    assert!(app.world.get::<KeptSelection>(kept).is_none());
+}
+
+#[test]
+fn clicking_empty_space_clears_a_kept_selection() {
+    let regions = GridRegions::split_board();
+    let at = tableau_pile_pos(&regions, 3);
+
+    for tap in [false, true] {
+        let mut app = board_app();
+        add_drag_systems(&mut app);
+        app.insert_resource(PersistSelection(true))
+            .insert_resource(TapToDeselect(tap))
+            .add_systems(Update, deselect_on_empty_tap.after(update_cursor));
+        let kept = drop_at(&mut app, tableau_pile_pos(&regions, 0), at);
+        assert!(app.world.get::<KeptSelection>(kept).is_some());
+
+        // Well clear of the dropped card, so the press picks nothing up.
+        let empty = at + vec2(0.0, 400.0);
+        move_pointer(&mut app, empty);
+        step(&mut app);
+        press(&mut app, MouseButton::Left);
+        step(&mut app);
+        assert_eq!(
+            app.world.get::<KeptSelection>(kept).is_none(),
+            tap,
+            "tap {tap}"
+        );
+        // The press still starts a box select.
+        assert_eq!(app.world.resource::<BoxSelect>().0, Some(empty));
+    }
+}
+
+#[test]
+fn a_big_deal_never_has_more_than_the_cap_in_the_air() {
+    const MAX: usize = 3;
+    let mut app = board_app();
+    app.insert_resource(MaxConcurrentDeals(MAX))
+        .insert_resource(DealTiming {
+            stagger: 0.0,
+            flight: 0.1,
+        })
+        .add_systems(Startup, deal_starting_tableau)
+        .add_systems(Update, (deal_animation, start_queued_deals).chain());
+    let count = |app: &mut App, filter: fn(&EntityRef) -> bool| {
+        app.world
+            .iter_entities()
+            .filter(|entity| filter(entity))
+            .count()
+    };
+
+    let mut busiest = 0;
+    for frame in 0..200 {
+        step(&mut app);
+        let flying = count(&mut app, |entity| entity.contains::<DealAnimation>());
+        assert!(flying <= MAX, "{flying} in the air on frame {frame}");
+        busiest = busiest.max(flying);
+    }
+    assert_eq!(busiest, MAX);
+
+    // All 28 tableau cards got there in the end.
+    assert_eq!(card_count(&mut app), 28);
+    assert_eq!(count(&mut app, |entity| entity.contains::<QueuedDeal>()), 0);
+    assert_eq!(
+        count(&mut app, |entity| entity.contains::<DealAnimation>()),
+        0
+    );
+}
+
+#[test]
+fn pile_badges_follow_the_top_card_and_keep_its_layer() {
+    let regions = GridRegions::split_board();
+    let pos = tableau_pile_pos(&regions, 1);
+    let mut app = board_app();
+    add_drag_systems(&mut app);
+    app.add_systems(Update, show_piles.after(index_piles));
+    let badges = |app: &mut App| {
+        app.world
+            .query_filtered::<(Entity, &Parent, &Text, &RenderLayers), With<PileBadge>>()
+            .iter(&app.world)
+            .map(|(badge, parent, text, layers)| {
+                (badge, parent.get(), text.sections[0].value.clone(), *layers)
+            })
+            .collect::<Vec<_>>()
+    };
+    let only_badge = |app: &mut App| {
+        let mut found = badges(app);
+        assert_eq!(found.len(), 1);
+        found.pop().unwrap()
+    };
+
+    let bottom = spawn_at(&mut app, pos, card(Rank::Ace, Suit::Clubs), Face::Up);
+    step_frames(&mut app, 3);
+    assert!(badges(&mut app).is_empty());
+
+    let middle = spawn_at(&mut app, pos, card(Rank::Two, Suit::Clubs), Face::Up);
+    step_frames(&mut app, 3);
+    let (badge, parent, count, layers) = only_badge(&mut app);
+    assert_eq!((parent, count.as_str()), (middle, "2"));
+    assert_eq!(layers, RenderLayers::layer(INTERACTIVE_LAYER));
+
+    // A new top card takes a new badge.
+    let top = spawn_at(&mut app, pos, card(Rank::Three, Suit::Clubs), Face::Up);
+    step_frames(&mut app, 3);
+    let (moved, parent, count, _) = only_badge(&mut app);
+    assert_ne!(moved, badge);
+    assert_eq!((parent, count.as_str()), (top, "3"));
+
+    // The same top card keeps its badge, with the count rewritten.
+    app.world.despawn(bottom);
+    step_frames(&mut app, 3);
+    let (kept, parent, count, _) = only_badge(&mut app);
+    assert_eq!((kept, parent, count.as_str()), (moved, top, "2"));
+
+    // A badge on the background layer is drawn there with its card.
+    app.insert_resource(CardLayer(BACKGROUND_LAYER));
+    let behind = tableau_pile_pos(&regions, 2);
+    spawn_at(&mut app, behind, card(Rank::Ace, Suit::Hearts), Face::Up);
+    spawn_at(&mut app, behind, card(Rank::Two, Suit::Hearts), Face::Up);
+    step_frames(&mut app, 3);
+    let layers = badges(&mut app)
+        .into_iter()
+        .map(|(_, _, _, layers)| layers)
+        .collect::<Vec<_>>();
+    assert!(layers.contains(&RenderLayers::layer(BACKGROUND_LAYER)));
+}
@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+const TOAST_DURATION: f32 = 2.0;
+
+/// Briefly shows a message at the top of the screen.
+#[derive(Event)]
+pub struct Toast(pub String);
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_systems(Startup, spawn_toast_text)
+            .add_systems(Update, show_toasts);
+    }
+}
+
+#[derive(Component)]
+struct ToastText(Timer);
+
+fn spawn_toast_text(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(90.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            let mut timer = Timer::from_seconds(TOAST_DURATION, TimerMode::Once);
+            timer.tick(timer.duration());
+
+            parent.spawn((
+                ToastText(timer),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 28.0,
+                        color: Color::YELLOW,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn show_toasts(
+    mut toasts: EventReader<Toast>,
+    mut query: Query<(&mut Text, &mut ToastText)>,
+    time: Res<Time>,
+) {
+    let latest = toasts.read().last();
+
+    for (mut text, mut toast) in &mut query {
+        if let Some(Toast(message)) = latest {
+            text.sections[0].value = message.clone();
+            toast.0.reset();
+        }
+
+        toast.0.tick(time.delta());
+
+        // Fade out over the last half of the toast's lifetime.
+        let alpha = (toast.0.percent_left() * 2.0).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::{CardDropped, CardPickedUp, CardSpawned, DespawnCard};
+
+/// Written once the tutorial is finished or skipped, so it only runs on first launch.
+const TUTORIAL_MARKER: &str = "tutorial_complete";
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Tutorial::new())
+            .add_systems(Startup, spawn_tutorial_text)
+            .add_systems(
+                Update,
+                (advance_tutorial.in_set(TutorialKeys), show_tutorial).chain(),
+            );
+    }
+}
+
+/// Reads the tutorial's keys. Escape skips the tutorial and is used up, so
+/// systems that also bind it run after this set and only see presses the
+/// tutorial left alone.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TutorialKeys;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TutorialTrigger {
+    Spawned,
+    PickedUp,
+    Dropped,
+    Discarded,
+}
+
+pub struct TutorialStep {
+    pub text: &'static str,
+    pub trigger: TutorialTrigger,
+}
+
+#[derive(Resource)]
+pub struct Tutorial {
+    pub steps: Vec<TutorialStep>,
+    pub current: usize,
+}
+
+impl Tutorial {
+    fn new() -> Self {
+        let steps = vec![
+            TutorialStep {
+                text: "Right-click empty space to spawn a card",
+                trigger: TutorialTrigger::Spawned,
+            },
+            TutorialStep {
+                text: "Left-click a card to pick it up",
+                trigger: TutorialTrigger::PickedUp,
+            },
+            TutorialStep {
+                text: "Drag it to a cell and release to drop it",
+                trigger: TutorialTrigger::Dropped,
+            },
+            TutorialStep {
+                text: "Fling a card into the bottom right corner to discard it",
+                trigger: TutorialTrigger::Discarded,
+            },
+        ];
+
+        let current = if Path::new(TUTORIAL_MARKER).exists() {
+            steps.len()
+        } else {
+            0
+        };
+
+        Self { steps, current }
+    }
+
+    fn current_step(&self) -> Option<&TutorialStep> {
+        self.steps.get(self.current)
+    }
+
+    fn finish(&mut self) {
+        self.current = self.steps.len();
+        if let Err(err) = std::fs::write(TUTORIAL_MARKER, "") {
+            warn!("could not record tutorial completion: {err}");
+        }
+    }
+
+    fn reset(&mut self) {
+        self.current = 0;
+        if let Err(err) = std::fs::remove_file(TUTORIAL_MARKER) {
+            warn!("could not reset tutorial: {err}");
+        }
+    }
+}
+
+#[derive(Component)]
+struct TutorialText;
+
+fn spawn_tutorial_text(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TutorialText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn advance_tutorial(
+    mut tutorial: ResMut<Tutorial>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut spawned: EventReader<CardSpawned>,
+    mut picked_up: EventReader<CardPickedUp>,
+    mut dropped: EventReader<CardDropped>,
+    mut discarded: EventReader<DespawnCard>,
+) {
+    let fired = [
+        (TutorialTrigger::Spawned, spawned.read().count() > 0),
+        (TutorialTrigger::PickedUp, picked_up.read().count() > 0),
+        (TutorialTrigger::Dropped, dropped.read().count() > 0),
+        (TutorialTrigger::Discarded, discarded.read().count() > 0),
+    ];
+
+    if keys.just_pressed(KeyCode::F2) {
+        tutorial.reset();
+        return;
+    }
+
+    let Some(trigger) = tutorial.current_step().map(|step| step.trigger) else {
+        return;
+    };
+
+    if keys.clear_just_pressed(KeyCode::Escape) {
+        tutorial.finish();
+        return;
+    }
+
+    if fired.contains(&(trigger, true)) {
+        tutorial.current += 1;
+        if tutorial.current_step().is_none() {
+            tutorial.finish();
+        }
+    }
+}
+
+fn show_tutorial(tutorial: Res<Tutorial>, mut query: Query<&mut Text, With<TutorialText>>) {
+    if !tutorial.is_changed() {
+        return;
+    }
+
+    let text = tutorial.current_step().map_or("", |step| step.text);
+    for mut tutorial_text in &mut query {
+        tutorial_text.sections[0].value = text.to_string();
+    }
+}